@@ -0,0 +1,163 @@
+//! Smoothed aggregation: coarse points are aggregates of strongly connected nodes,
+//! the prolongator interpolates the near-nullspace exactly on each aggregate and is
+//! smoothed by a damped Jacobi step.
+//!
+//! Systems with `block_size` unknowns per node are coarsened node by node, the
+//! unknowns of node `i` being `i * block_size .. (i + 1) * block_size`.
+
+use nalgebra::DMatrix;
+use nalgebra_sparse::csr::CsrMatrix;
+use crate::vector::{diagonal, norm, spmv};
+
+/// Greedy aggregation of the strength graph `s`: first the nodes whose strong
+/// neighbours are all free form aggregates with them, then the remaining nodes join
+/// a neighbouring aggregate, the last ones aggregate with their free neighbours.
+/// Nodes without strong connections stay out of any aggregate (`usize::MAX`).
+/// Returns the aggregate of each node and the number of aggregates.
+pub fn standard_aggregation(s: &CsrMatrix<f64>) -> (Vec<usize>, usize) {
+    const FREE: usize = usize::MAX;
+    let n = s.nrows();
+    let mut aggregate = vec![FREE; n];
+    let mut count = 0;
+    let (offsets, cols) = (s.row_offsets(), s.col_indices());
+    let neighbours = |i: usize| cols[offsets[i]..offsets[i + 1]].iter().cloned().filter(move |&j| j != i);
+
+    for i in 0..n {
+        if aggregate[i] != FREE || neighbours(i).next().is_none() {
+            continue;
+        }
+        if neighbours(i).all(|j| aggregate[j] == FREE) {
+            aggregate[i] = count;
+            for j in neighbours(i) {
+                aggregate[j] = count;
+            }
+            count += 1;
+        }
+    }
+
+    // joined in a separate sweep so that new members do not attract further nodes
+    let first = aggregate.clone();
+    for (i, g) in aggregate.iter_mut().enumerate() {
+        if *g == FREE {
+            if let Some(j) = neighbours(i).find(|&j| first[j] != FREE) {
+                *g = first[j];
+            }
+        }
+    }
+
+    for i in 0..n {
+        if aggregate[i] != FREE || neighbours(i).next().is_none() {
+            continue;
+        }
+        aggregate[i] = count;
+        for j in neighbours(i) {
+            if aggregate[j] == FREE {
+                aggregate[j] = count;
+            }
+        }
+        count += 1;
+    }
+    (aggregate, count)
+}
+
+/// The constant vectors of each of the `block_size` unknowns of the `n` rows.
+pub fn constant_nullspace(n: usize, block_size: usize) -> DMatrix<f64> {
+    DMatrix::from_fn(n, block_size, |i, c| if i % block_size == c { 1.0 } else { 0.0 })
+}
+
+/// The tentative prolongator: on every aggregate the rows of the near-nullspace `b`
+/// orthonormalized (Gram–Schmidt, dependent columns zero), so that `P Bc = B` for the
+/// coarse near-nullspace `Bc` of the `R` factors. Returns `P` and `Bc`.
+pub fn tentative_prolongator(
+    aggregate: &[usize],
+    count: usize,
+    b: &DMatrix<f64>,
+    block_size: usize,
+) -> (CsrMatrix<f64>, DMatrix<f64>) {
+    let (n, k) = b.shape();
+    let mut members = vec![Vec::new(); count];
+    for (node, &agg) in aggregate.iter().enumerate() {
+        if agg != usize::MAX {
+            members[agg].extend(node * block_size..(node + 1) * block_size);
+        }
+    }
+    // the columns of P restricted to each row, row `r` in aggregate `aggregate[r / bs]`
+    let mut q = DMatrix::<f64>::zeros(n, k);
+    let mut bc = DMatrix::<f64>::zeros(count * k, k);
+    for (agg, rows) in members.iter().enumerate() {
+        for j in 0..k {
+            let norm_b = rows.iter().map(|&r| b[(r, j)] * b[(r, j)]).sum::<f64>().sqrt();
+            for &r in rows {
+                q[(r, j)] = b[(r, j)];
+            }
+            for l in 0..j {
+                let dot: f64 = rows.iter().map(|&r| q[(r, l)] * q[(r, j)]).sum();
+                bc[(agg * k + l, j)] = dot;
+                for &r in rows {
+                    q[(r, j)] -= dot * q[(r, l)];
+                }
+            }
+            let norm = rows.iter().map(|&r| q[(r, j)] * q[(r, j)]).sum::<f64>().sqrt();
+            let (scale, diagonal) = if norm > 1e-10 * norm_b { (1.0 / norm, norm) } else { (0.0, 0.0) };
+            bc[(agg * k + j, j)] = diagonal;
+            for &r in rows {
+                q[(r, j)] *= scale;
+            }
+        }
+    }
+    let mut offsets = Vec::with_capacity(n + 1);
+    let mut cols = Vec::new();
+    let mut vals = Vec::new();
+    offsets.push(0);
+    for r in 0..n {
+        let agg = aggregate[r / block_size];
+        if agg != usize::MAX {
+            for j in 0..k {
+                cols.push(agg * k + j);
+                vals.push(q[(r, j)]);
+            }
+        }
+        offsets.push(cols.len());
+    }
+    (CsrMatrix::try_from_csr_data(n, count * k, offsets, cols, vals).unwrap(), bc)
+}
+
+/// The spectral radius of `D^-1 A` estimated by power iterations.
+pub fn spectral_radius_dinv_a(a: &CsrMatrix<f64>, iterations: usize) -> f64 {
+    let n = a.nrows();
+    let dinv: Vec<f64> = diagonal(a).iter().map(|&d| if d != 0.0 { 1.0 / d } else { 0.0 }).collect();
+    // a fixed pseudo-random vector, with components along all the eigenvectors in practice
+    let mut x: Vec<f64> = (0..n as u64)
+        .map(|i| (i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 11) as f64 / (1u64 << 53) as f64 - 0.5)
+        .collect();
+    let mut y = vec![0.0; n];
+    let mut rho = 0.0;
+    for _ in 0..iterations {
+        let nx = norm(&x);
+        if nx == 0.0 {
+            break;
+        }
+        spmv(a, &x, &mut y);
+        for (yi, d) in y.iter_mut().zip(&dinv) {
+            *yi *= d;
+        }
+        rho = norm(&y) / nx;
+        std::mem::swap(&mut x, &mut y);
+    }
+    rho
+}
+
+/// Jacobi smoothing of the tentative prolongator, `P = (I - omega / rho D^-1 A) P_tent`
+/// with `rho` the spectral radius of `D^-1 A`.
+pub fn smooth_prolongator(a: &CsrMatrix<f64>, tentative: &CsrMatrix<f64>, omega: f64) -> CsrMatrix<f64> {
+    let rho = spectral_radius_dinv_a(a, 15);
+    let mut dinv_a = a.clone();
+    for (i, mut row) in dinv_a.row_iter_mut().enumerate() {
+        let d = a.get_entry(i, i).map_or(0.0, |e| e.into_value());
+        let scale = if d != 0.0 { omega / (rho * d) } else { 0.0 };
+        for v in row.values_mut() {
+            *v *= scale;
+        }
+    }
+    tentative - &(&dinv_a * tentative)
+}
@@ -0,0 +1,151 @@
+//! The AMG solve phase: multigrid cycles over the hierarchy.
+
+use std::str::FromStr;
+use nalgebra::{DMatrix, DVector, Dyn, LU};
+use super::hierarchy::Hierarchy;
+use super::smoother::{LevelSmoother, Smoother};
+use crate::vector::{norm, residual, spmv};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cycle {
+    /// one coarse grid correction per level
+    V,
+    /// two coarse grid corrections per level
+    W,
+    /// an F-cycle then a V-cycle on the coarser level
+    F,
+}
+
+impl FromStr for Cycle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "v" => Ok(Cycle::V),
+            "w" => Ok(Cycle::W),
+            "f" => Ok(Cycle::F),
+            _ => Err(format!("unknown cycle `{}`, expected v|w|f", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SolveParams {
+    pub cycle: Cycle,
+    pub smoother: Smoother,
+    pub pre_sweeps: usize,
+    pub post_sweeps: usize,
+    /// damped Jacobi weight
+    pub jacobi_weight: f64,
+    pub chebyshev_degree: usize,
+    pub max_cycles: usize,
+    /// on the residual relative to the right hand side
+    pub tolerance: f64,
+    /// the most rows of the coarsest level for its dense LU factors
+    pub max_direct: usize,
+}
+
+impl Default for SolveParams {
+    fn default() -> Self {
+        SolveParams {
+            cycle: Cycle::V,
+            smoother: Smoother::SymmetricGaussSeidel,
+            pre_sweeps: 1,
+            post_sweeps: 1,
+            jacobi_weight: 2.0 / 3.0,
+            chebyshev_degree: 3,
+            max_cycles: 100,
+            tolerance: 1e-8,
+            max_direct: 4000,
+        }
+    }
+}
+
+/// A hierarchy ready for solving: the smoothers of its levels and the LU factors of
+/// the coarsest operator.
+pub struct Multigrid {
+    pub hierarchy: Hierarchy,
+    pub params: SolveParams,
+    smoothers: Vec<LevelSmoother>,
+    coarse: LU<f64, Dyn, Dyn>,
+}
+
+impl Multigrid {
+    pub fn new(hierarchy: Hierarchy, params: SolveParams) -> Result<Self, String> {
+        let n = hierarchy.levels.len();
+        let rows = hierarchy.levels[n - 1].a.nrows();
+        if rows > params.max_direct {
+            return Err(format!(
+                "the coarsest level has {} rows, more than {} for a direct solve: coarsening stopped early",
+                rows, params.max_direct
+            ));
+        }
+        let smoothers = hierarchy.levels[..n - 1]
+            .iter()
+            .map(|l| LevelSmoother::new(&l.a, params.smoother, params.jacobi_weight, params.chebyshev_degree))
+            .collect();
+        let coarse = DMatrix::from(&hierarchy.levels[n - 1].a).lu();
+        if !coarse.is_invertible() {
+            return Err(String::from("the coarsest operator is singular"));
+        }
+        Ok(Multigrid { hierarchy, params, smoothers, coarse })
+    }
+
+    /// One cycle on `A x = b` of the finest level, improving `x`.
+    pub fn cycle(&self, b: &[f64], x: &mut [f64]) {
+        self.visit(0, self.params.cycle, b, x);
+    }
+
+    fn visit(&self, k: usize, cycle: Cycle, b: &[f64], x: &mut [f64]) {
+        let level = &self.hierarchy.levels[k];
+        let (p, r) = match (&level.p, &level.r) {
+            (Some(p), Some(r)) => (p, r),
+            _ => {
+                let solution = self.coarse.solve(&DVector::from_column_slice(b)).unwrap();
+                x.copy_from_slice(solution.as_slice());
+                return;
+            }
+        };
+        let smoother = &self.smoothers[k];
+        smoother.smooth(&level.a, b, x, self.params.pre_sweeps);
+        let visits: &[Cycle] = match cycle {
+            Cycle::V => &[Cycle::V],
+            Cycle::W => &[Cycle::W, Cycle::W],
+            Cycle::F => &[Cycle::F, Cycle::V],
+        };
+        let mut residue = vec![0.0; x.len()];
+        let mut bc = vec![0.0; p.ncols()];
+        let mut xc = vec![0.0; p.ncols()];
+        let mut correction = vec![0.0; x.len()];
+        for &c in visits {
+            residual(&level.a, b, x, &mut residue);
+            spmv(r, &residue, &mut bc);
+            xc.iter_mut().for_each(|v| *v = 0.0);
+            self.visit(k + 1, c, &bc, &mut xc);
+            spmv(p, &xc, &mut correction);
+            for (xi, ci) in x.iter_mut().zip(&correction) {
+                *xi += ci;
+            }
+        }
+        smoother.smooth(&level.a, b, x, self.params.post_sweeps);
+    }
+
+    /// Cycles from the initial guess `x` until the residual relative to `b` (to the
+    /// initial one for `b = 0`) is below the tolerance or `max_cycles` are done.
+    /// Returns the residual norms, the initial one and one per cycle.
+    pub fn solve(&self, b: &[f64], x: &mut [f64]) -> Vec<f64> {
+        let a = &self.hierarchy.levels[0].a;
+        let mut r = vec![0.0; x.len()];
+        residual(a, b, x, &mut r);
+        let mut history = vec![norm(&r)];
+        let reference = if norm(b) > 0.0 { norm(b) } else { history[0] };
+        while history.len() <= self.params.max_cycles
+            && history[history.len() - 1] > self.params.tolerance * reference
+        {
+            self.cycle(b, x);
+            residual(a, b, x, &mut r);
+            history.push(norm(&r));
+        }
+        history
+    }
+}
@@ -0,0 +1,219 @@
+//! The AMG setup phase: the hierarchy of coarse grid operators.
+
+use std::fmt;
+use std::str::FromStr;
+use nalgebra::DMatrix;
+use nalgebra_sparse::csr::CsrMatrix;
+use super::aggregation::{constant_nullspace, smooth_prolongator, standard_aggregation, tentative_prolongator};
+use super::interpolation::{classical_interpolation, direct_interpolation};
+use super::splitting::{pmis, ruge_stuben, Point};
+use super::strength::{classical_strength, symmetric_strength};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    /// classical (Ruge–Stüben) AMG: C/F splitting and interpolation
+    Classical,
+    /// smoothed aggregation
+    SmoothedAggregation,
+}
+
+impl FromStr for Method {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "classical" => Ok(Method::Classical),
+            "sa" => Ok(Method::SmoothedAggregation),
+            _ => Err(format!("unknown method `{}`, expected classical|sa", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Coarsening {
+    /// Ruge–Stüben first and second pass
+    RugeStuben,
+    /// parallel modified independent set
+    Pmis,
+}
+
+impl FromStr for Coarsening {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "rs" => Ok(Coarsening::RugeStuben),
+            "pmis" => Ok(Coarsening::Pmis),
+            _ => Err(format!("unknown coarsening `{}`, expected rs|pmis", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    Classical,
+    Direct,
+}
+
+impl FromStr for Interpolation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "classical" => Ok(Interpolation::Classical),
+            "direct" => Ok(Interpolation::Direct),
+            _ => Err(format!("unknown interpolation `{}`, expected classical|direct", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AmgParams {
+    pub method: Method,
+    /// strength of connection threshold
+    pub theta: f64,
+    pub coarsening: Coarsening,
+    pub interpolation: Interpolation,
+    pub max_levels: usize,
+    /// no further coarsening of levels with at most this many rows
+    pub max_coarse: usize,
+    /// random seed of PMIS
+    pub seed: u64,
+    /// unknowns per node, aggregated together by smoothed aggregation
+    pub block_size: usize,
+    /// prolongator smoothing weight relative to the spectral radius of `D^-1 A`
+    pub omega: f64,
+}
+
+impl Default for AmgParams {
+    fn default() -> Self {
+        AmgParams {
+            method: Method::Classical,
+            theta: 0.25,
+            coarsening: Coarsening::RugeStuben,
+            interpolation: Interpolation::Classical,
+            max_levels: 25,
+            max_coarse: 50,
+            seed: 1,
+            block_size: 1,
+            omega: 4.0 / 3.0,
+        }
+    }
+}
+
+impl AmgParams {
+    /// Smoothed aggregation with its usual, weaker strength threshold.
+    pub fn smoothed_aggregation() -> Self {
+        AmgParams { method: Method::SmoothedAggregation, theta: 0.08, ..AmgParams::default() }
+    }
+}
+
+pub struct Level {
+    pub a: CsrMatrix<f64>,
+    /// interpolation from the next coarser level, none on the coarsest one
+    pub p: Option<CsrMatrix<f64>>,
+    /// restriction to the next coarser level, `P^T`
+    pub r: Option<CsrMatrix<f64>>,
+}
+
+pub struct Hierarchy {
+    pub levels: Vec<Level>,
+}
+
+impl Hierarchy {
+    /// Coarsens `a` until `max_levels` levels, a level of at most `max_coarse` rows or
+    /// a splitting without coarse (or fine) points; coarse operators are `R A P`.
+    /// Smoothed aggregation uses the constant near-nullspace of each unknown of a node.
+    pub fn new(a: CsrMatrix<f64>, params: &AmgParams) -> Result<Self, String> {
+        Self::with_nullspace(a, params, None)
+    }
+
+    /// As `new`, smoothed aggregation interpolating the near-nullspace `b` (one column
+    /// per vector) exactly on the aggregates.
+    pub fn with_nullspace(a: CsrMatrix<f64>, params: &AmgParams, b: Option<DMatrix<f64>>) -> Result<Self, String> {
+        if a.nrows() != a.ncols() {
+            return Err(format!("the matrix is not square: {} x {}", a.nrows(), a.ncols()));
+        }
+        if params.block_size == 0 || !a.nrows().is_multiple_of(params.block_size) {
+            return Err(format!("{} rows are not a multiple of the block size {}", a.nrows(), params.block_size));
+        }
+        let mut b = b.unwrap_or_else(|| constant_nullspace(a.nrows(), params.block_size));
+        if b.nrows() != a.nrows() || b.ncols() == 0 {
+            return Err(format!("the near-nullspace is {} x {} for {} rows", b.nrows(), b.ncols(), a.nrows()));
+        }
+        let mut block_size = params.block_size;
+        let mut levels = Vec::new();
+        let mut a = a;
+        while levels.len() + 1 < params.max_levels && a.nrows() > params.max_coarse {
+            let p = match params.method {
+                Method::Classical => {
+                    let s = classical_strength(&a, params.theta);
+                    let splitting = match params.coarsening {
+                        Coarsening::RugeStuben => ruge_stuben(&s),
+                        Coarsening::Pmis => pmis(&s, params.seed + levels.len() as u64),
+                    };
+                    let nc = splitting.iter().filter(|&&p| p == Point::Coarse).count();
+                    if nc == 0 || nc == a.nrows() {
+                        break;
+                    }
+                    match params.interpolation {
+                        Interpolation::Classical => classical_interpolation(&a, &s, &splitting),
+                        Interpolation::Direct => direct_interpolation(&a, &s, &splitting),
+                    }
+                }
+                Method::SmoothedAggregation => {
+                    let s = symmetric_strength(&a, params.theta, block_size);
+                    let (aggregate, count) = standard_aggregation(&s);
+                    if count == 0 || count * b.ncols() >= a.nrows() {
+                        break;
+                    }
+                    let (tentative, coarse_b) = tentative_prolongator(&aggregate, count, &b, block_size);
+                    // coarse nodes carry one unknown per near-nullspace vector
+                    block_size = b.ncols();
+                    b = coarse_b;
+                    smooth_prolongator(&a, &tentative, params.omega)
+                }
+            };
+            let r = p.transpose();
+            let coarse = &(&r * &a) * &p;
+            levels.push(Level { a, p: Some(p), r: Some(r) });
+            a = coarse;
+        }
+        levels.push(Level { a, p: None, r: None });
+        Ok(Hierarchy { levels })
+    }
+
+    /// Total number of rows of all the levels relative to the finest one.
+    pub fn grid_complexity(&self) -> f64 {
+        let rows: usize = self.levels.iter().map(|l| l.a.nrows()).sum();
+        rows as f64 / self.levels[0].a.nrows() as f64
+    }
+
+    /// Total number of nonzeros of all the operators relative to the finest one.
+    pub fn operator_complexity(&self) -> f64 {
+        let nnz: usize = self.levels.iter().map(|l| l.a.nnz()).sum();
+        nnz as f64 / self.levels[0].a.nnz() as f64
+    }
+}
+
+impl fmt::Display for Hierarchy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>5} {:>10} {:>12} {:>8}", "level", "rows", "nonzeros", "nnz/row")?;
+        for (k, l) in self.levels.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>5} {:>10} {:>12} {:>8.1}",
+                k,
+                l.a.nrows(),
+                l.a.nnz(),
+                l.a.nnz() as f64 / l.a.nrows().max(1) as f64
+            )?;
+        }
+        write!(
+            f,
+            "grid complexity: {:.3} operator complexity: {:.3}",
+            self.grid_complexity(),
+            self.operator_complexity()
+        )
+    }
+}
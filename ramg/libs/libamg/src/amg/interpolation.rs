@@ -0,0 +1,161 @@
+//! Interpolation from the coarse points to all the fine grid points.
+//!
+//! The prolongator `P` has one row per fine grid point and one column per coarse
+//! point: coarse points keep their value, fine points interpolate from the coarse
+//! points they strongly depend on.
+
+use nalgebra_sparse::csr::CsrMatrix;
+use super::splitting::Point;
+
+/// The coarse grid index of each coarse point, `usize::MAX` for the others.
+pub fn coarse_indices(splitting: &[Point]) -> (Vec<usize>, usize) {
+    let mut nc = 0;
+    let index = splitting
+        .iter()
+        .map(|&p| {
+            if p == Point::Coarse {
+                nc += 1;
+                nc - 1
+            } else {
+                usize::MAX
+            }
+        })
+        .collect();
+    (index, nc)
+}
+
+fn diagonal(a: &CsrMatrix<f64>, i: usize) -> f64 {
+    a.get_entry(i, i).map_or(0.0, |e| e.into_value())
+}
+
+// assembles P row by row, `row(i, push)` pushes the (coarse column, weight) pairs of fine point `i`
+fn prolongator<F>(splitting: &[Point], mut row: F) -> CsrMatrix<f64>
+where
+    F: FnMut(usize, &mut dyn FnMut(usize, f64)),
+{
+    let (index, nc) = coarse_indices(splitting);
+    let n = splitting.len();
+    let mut offsets = Vec::with_capacity(n + 1);
+    let mut cols = Vec::new();
+    let mut vals = Vec::new();
+    offsets.push(0);
+    for i in 0..n {
+        if splitting[i] == Point::Coarse {
+            cols.push(index[i]);
+            vals.push(1.0);
+        } else {
+            let mut entries = Vec::new();
+            row(i, &mut |j, w| entries.push((index[j], w)));
+            entries.sort_by_key(|&(c, _)| c);
+            for (c, w) in entries {
+                cols.push(c);
+                vals.push(w);
+            }
+        }
+        offsets.push(cols.len());
+    }
+    CsrMatrix::try_from_csr_data(n, nc, offsets, cols, vals).unwrap()
+}
+
+/// Direct interpolation: the couplings of a fine point to all its neighbours are
+/// distributed to its strong coarse neighbours, negative and positive couplings
+/// separately; positive couplings are lumped to the diagonal when no strong
+/// coarse neighbour has one.
+pub fn direct_interpolation(a: &CsrMatrix<f64>, s: &CsrMatrix<f64>, splitting: &[Point]) -> CsrMatrix<f64> {
+    prolongator(splitting, |i, push| {
+        let (mut all_neg, mut all_pos, mut strong_neg, mut strong_pos) = (0.0, 0.0, 0.0, 0.0);
+        let row = a.row(i);
+        for (&j, &v) in row.col_indices().iter().zip(row.values()) {
+            if j != i {
+                if v < 0.0 { all_neg += v } else { all_pos += v }
+            }
+        }
+        let strong = s.row(i);
+        let coarse = || {
+            strong
+                .col_indices()
+                .iter()
+                .zip(strong.values())
+                .filter(|&(&j, _)| j != i && splitting[j] == Point::Coarse)
+        };
+        for (_, &v) in coarse() {
+            if v < 0.0 { strong_neg += v } else { strong_pos += v }
+        }
+        let mut d = diagonal(a, i);
+        let alpha = if strong_neg != 0.0 { all_neg / strong_neg } else { 0.0 };
+        let beta = if strong_pos != 0.0 {
+            all_pos / strong_pos
+        } else {
+            d += all_pos;
+            0.0
+        };
+        for (&j, &v) in coarse() {
+            let w = if v < 0.0 { alpha } else { beta };
+            push(j, -w * v / d);
+        }
+    })
+}
+
+/// Classical (Ruge–Stüben) interpolation: strong fine neighbours `m` of a fine point
+/// are distributed to its strong coarse neighbours in proportion to their couplings
+/// `a_mj` of sign opposite to `a_mm`, weak couplings are lumped to the diagonal.
+pub fn classical_interpolation(a: &CsrMatrix<f64>, s: &CsrMatrix<f64>, splitting: &[Point]) -> CsrMatrix<f64> {
+    let n = a.nrows();
+    // position of the strong coarse neighbours of the current fine point in `numerator`
+    let mut slot = vec![usize::MAX; n];
+    let mut strong = vec![false; n];
+    prolongator(splitting, |i, push| {
+        let srow = s.row(i);
+        let mut coarse = Vec::new();
+        for &j in srow.col_indices() {
+            if j != i {
+                strong[j] = true;
+                if splitting[j] == Point::Coarse {
+                    slot[j] = coarse.len();
+                    coarse.push(j);
+                }
+            }
+        }
+        let mut numerator = vec![0.0; coarse.len()];
+        let mut d = 0.0;
+        let row = a.row(i);
+        for (&m, &v) in row.col_indices().iter().zip(row.values()) {
+            if m == i {
+                d += v;
+            } else if !strong[m] {
+                // weak connection
+                d += v;
+            } else if splitting[m] == Point::Coarse {
+                numerator[slot[m]] += v;
+            } else {
+                // strong fine neighbour: distribute a_im to the coarse points of `i`
+                let sign = diagonal(a, m).signum();
+                let mrow = a.row(m);
+                let hat = |k: usize, w: f64| slot[k] != usize::MAX && w * sign < 0.0 && k != m;
+                let sum: f64 = mrow
+                    .col_indices()
+                    .iter()
+                    .zip(mrow.values())
+                    .filter(|&(&k, &w)| hat(k, w))
+                    .map(|(_, &w)| w)
+                    .sum();
+                if sum == 0.0 {
+                    d += v;
+                } else {
+                    for (&k, &w) in mrow.col_indices().iter().zip(mrow.values()) {
+                        if hat(k, w) {
+                            numerator[slot[k]] += v * w / sum;
+                        }
+                    }
+                }
+            }
+        }
+        for (&j, &num) in coarse.iter().zip(&numerator) {
+            push(j, -num / d);
+        }
+        for &j in srow.col_indices() {
+            strong[j] = false;
+            slot[j] = usize::MAX;
+        }
+    })
+}
@@ -0,0 +1,11 @@
+pub use self::hierarchy::{AmgParams, Coarsening, Hierarchy, Interpolation, Level, Method};
+pub use self::cycle::{Cycle, Multigrid, SolveParams};
+pub use self::smoother::Smoother;
+pub use self::splitting::Point;
+pub mod strength;
+pub mod splitting;
+pub mod interpolation;
+pub mod aggregation;
+pub mod hierarchy;
+pub mod smoother;
+pub mod cycle;
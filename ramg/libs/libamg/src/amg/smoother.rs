@@ -0,0 +1,145 @@
+//! Smoothers: a few cheap iterations damping the error components the coarse grid
+//! correction cannot represent.
+
+use std::str::FromStr;
+use nalgebra_sparse::csr::CsrMatrix;
+use super::aggregation::spectral_radius_dinv_a;
+use crate::vector::{diagonal, residual};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Smoother {
+    /// damped Jacobi
+    Jacobi,
+    /// forward Gauss–Seidel
+    GaussSeidel,
+    /// backward Gauss–Seidel
+    BackwardGaussSeidel,
+    /// a forward then a backward Gauss–Seidel sweep
+    SymmetricGaussSeidel,
+    /// Jacobi with the l1 norms of the rows as the diagonal, convergent without damping
+    L1Jacobi,
+    /// Chebyshev polynomial in `D^-1 A`
+    Chebyshev,
+}
+
+impl FromStr for Smoother {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "jacobi" => Ok(Smoother::Jacobi),
+            "gs" => Ok(Smoother::GaussSeidel),
+            "backward-gs" => Ok(Smoother::BackwardGaussSeidel),
+            "symmetric-gs" => Ok(Smoother::SymmetricGaussSeidel),
+            "l1-jacobi" => Ok(Smoother::L1Jacobi),
+            "chebyshev" => Ok(Smoother::Chebyshev),
+            _ => Err(format!(
+                "unknown smoother `{}`, expected jacobi|gs|backward-gs|symmetric-gs|l1-jacobi|chebyshev",
+                s
+            )),
+        }
+    }
+}
+
+/// The smoother of one level with what it needs of the matrix.
+pub struct LevelSmoother {
+    kind: Smoother,
+    /// inverse of the diagonal (l1 row norms for l1-Jacobi), zero for zero rows
+    dinv: Vec<f64>,
+    /// Jacobi damping
+    weight: f64,
+    /// Chebyshev degree and bounds of the eigenvalues of `D^-1 A` it damps
+    degree: usize,
+    bounds: (f64, f64),
+}
+
+fn inverse(d: f64) -> f64 {
+    if d != 0.0 { 1.0 / d } else { 0.0 }
+}
+
+impl LevelSmoother {
+    /// Chebyshev targets the upper part `[0.3 rho, rho]` of the spectrum of `D^-1 A`,
+    /// `rho` 1.1 times its power iterations estimate or the Gershgorin bound.
+    pub fn new(a: &CsrMatrix<f64>, kind: Smoother, weight: f64, degree: usize) -> Self {
+        let dinv = match kind {
+            Smoother::L1Jacobi => a
+                .row_iter()
+                .map(|row| inverse(row.values().iter().map(|v| v.abs()).sum()))
+                .collect(),
+            _ => diagonal(a).into_iter().map(inverse).collect(),
+        };
+        let bounds = if kind == Smoother::Chebyshev {
+            // Gershgorin bounds the estimate, which may well be too low, from above
+            let gershgorin = a
+                .row_iter()
+                .zip(&dinv)
+                .map(|(row, d): (_, &f64)| d.abs() * row.values().iter().map(|v| v.abs()).sum::<f64>())
+                .fold(0.0, f64::max);
+            let rho = (1.1 * spectral_radius_dinv_a(a, 15)).min(gershgorin);
+            (0.3 * rho, rho)
+        } else {
+            (0.0, 0.0)
+        };
+        LevelSmoother { kind, dinv, weight, degree, bounds }
+    }
+
+    /// `sweeps` smoothing iterations on `A x = b`.
+    pub fn smooth(&self, a: &CsrMatrix<f64>, b: &[f64], x: &mut [f64], sweeps: usize) {
+        let mut r = vec![0.0; x.len()];
+        for _ in 0..sweeps {
+            match self.kind {
+                Smoother::Jacobi => self.jacobi(a, b, x, self.weight, &mut r),
+                Smoother::L1Jacobi => self.jacobi(a, b, x, 1.0, &mut r),
+                Smoother::GaussSeidel => self.gauss_seidel(a, b, x, 0..a.nrows()),
+                Smoother::BackwardGaussSeidel => self.gauss_seidel(a, b, x, (0..a.nrows()).rev()),
+                Smoother::SymmetricGaussSeidel => {
+                    self.gauss_seidel(a, b, x, 0..a.nrows());
+                    self.gauss_seidel(a, b, x, (0..a.nrows()).rev());
+                }
+                Smoother::Chebyshev => self.chebyshev(a, b, x, &mut r),
+            }
+        }
+    }
+
+    fn jacobi(&self, a: &CsrMatrix<f64>, b: &[f64], x: &mut [f64], weight: f64, r: &mut [f64]) {
+        residual(a, b, x, r);
+        for ((xi, ri), d) in x.iter_mut().zip(r.iter()).zip(&self.dinv) {
+            *xi += weight * d * ri;
+        }
+    }
+
+    fn gauss_seidel<I: Iterator<Item = usize>>(&self, a: &CsrMatrix<f64>, b: &[f64], x: &mut [f64], order: I) {
+        for i in order {
+            let row = a.row(i);
+            let ri = b[i] - row.col_indices().iter().zip(row.values()).map(|(&j, &v)| v * x[j]).sum::<f64>();
+            x[i] += self.dinv[i] * ri;
+        }
+    }
+
+    // Chebyshev iteration preconditioned by the diagonal (Saad, Algorithm 12.1)
+    fn chebyshev(&self, a: &CsrMatrix<f64>, b: &[f64], x: &mut [f64], r: &mut [f64]) {
+        let (lower, upper) = self.bounds;
+        if upper == 0.0 {
+            return;
+        }
+        let (theta, delta) = ((upper + lower) / 2.0, (upper - lower) / 2.0);
+        let sigma = theta / delta;
+        let mut rho = 1.0 / sigma;
+        residual(a, b, x, r);
+        let mut d: Vec<f64> = r.iter().zip(&self.dinv).map(|(ri, di)| di * ri / theta).collect();
+        for k in 0..self.degree {
+            for (xi, di) in x.iter_mut().zip(&d) {
+                *xi += di;
+            }
+            if k + 1 == self.degree {
+                break;
+            }
+            residual(a, b, x, r);
+            let rho_new = 1.0 / (2.0 * sigma - rho);
+            for ((di, ri), dinv) in d.iter_mut().zip(r.iter()).zip(&self.dinv) {
+                *di = rho_new * rho * *di + 2.0 * rho_new / delta * dinv * ri;
+            }
+            rho = rho_new;
+        }
+    }
+}
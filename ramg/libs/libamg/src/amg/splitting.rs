@@ -0,0 +1,158 @@
+//! C/F splitting: choosing the coarse grid points among the fine grid ones.
+
+use std::collections::BinaryHeap;
+use nalgebra_sparse::csr::CsrMatrix;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Point {
+    Undecided,
+    Coarse,
+    Fine,
+}
+
+/// The number of points strongly depending on each point, `S^T` row lengths.
+fn influence(st: &CsrMatrix<f64>) -> Vec<usize> {
+    st.row_offsets().windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+/// Ruge–Stüben first pass: repeatedly the undecided point with the most undecided
+/// or fine points strongly depending on it becomes coarse and the points strongly
+/// depending on it fine. Points nobody depends on are fine from the start.
+pub fn rs_first_pass(s: &CsrMatrix<f64>) -> Vec<Point> {
+    let n = s.nrows();
+    let st = s.transpose();
+    let mut lambda = influence(&st);
+    let mut splitting = vec![Point::Undecided; n];
+    let mut heap = BinaryHeap::with_capacity(n);
+    for i in 0..n {
+        if lambda[i] == 0 {
+            splitting[i] = Point::Fine;
+        } else {
+            heap.push((lambda[i], i));
+        }
+    }
+    // entries with an outdated lambda are skipped, the current one was pushed again
+    while let Some((l, i)) = heap.pop() {
+        if splitting[i] != Point::Undecided || l != lambda[i] {
+            continue;
+        }
+        splitting[i] = Point::Coarse;
+        for &j in st.row(i).col_indices() {
+            if splitting[j] == Point::Undecided {
+                splitting[j] = Point::Fine;
+                // the points new fine ones depend on become more attractive
+                for &k in s.row(j).col_indices() {
+                    if splitting[k] == Point::Undecided {
+                        lambda[k] += 1;
+                        heap.push((lambda[k], k));
+                    }
+                }
+            }
+        }
+        for &j in s.row(i).col_indices() {
+            if splitting[j] == Point::Undecided && lambda[j] > 0 {
+                lambda[j] -= 1;
+                heap.push((lambda[j], j));
+            }
+        }
+    }
+    splitting
+}
+
+/// Ruge–Stüben second pass: every pair of strongly connected fine points gets a
+/// common coarse point they strongly depend on, adding coarse points where needed.
+pub fn rs_second_pass(s: &CsrMatrix<f64>, splitting: &mut [Point]) {
+    // marker[k] == i when k is in the coarse interpolatory set of i
+    let mut marker = vec![usize::MAX; splitting.len()];
+    for i in 0..splitting.len() {
+        if splitting[i] != Point::Fine {
+            continue;
+        }
+        for &k in s.row(i).col_indices() {
+            if splitting[k] == Point::Coarse {
+                marker[k] = i;
+            }
+        }
+        let mut tentative = None;
+        for &j in s.row(i).col_indices() {
+            if j == i || splitting[j] != Point::Fine {
+                continue;
+            }
+            if s.row(j).col_indices().iter().any(|&k| marker[k] == i) {
+                continue;
+            }
+            match tentative {
+                // a second fine neighbour without a common coarse point: `i` itself
+                // becomes coarse instead
+                Some(t) => {
+                    splitting[t] = Point::Fine;
+                    splitting[i] = Point::Coarse;
+                    break;
+                }
+                None => {
+                    tentative = Some(j);
+                    splitting[j] = Point::Coarse;
+                    marker[j] = i;
+                }
+            }
+        }
+    }
+}
+
+/// The classical Ruge–Stüben splitting, both passes.
+pub fn ruge_stuben(s: &CsrMatrix<f64>) -> Vec<Point> {
+    let mut splitting = rs_first_pass(s);
+    rs_second_pass(s, &mut splitting);
+    splitting
+}
+
+// xorshift64* uniform on [0, 1)
+fn random(state: &mut u64) -> f64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    (state.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Parallel modified independent set: the undecided points with a larger weight
+/// `lambda + random` than all their undecided strong neighbours become coarse,
+/// the undecided points strongly depending on them fine, until none is left.
+pub fn pmis(s: &CsrMatrix<f64>, seed: u64) -> Vec<Point> {
+    let n = s.nrows();
+    let st = s.transpose();
+    let mut state = seed.max(1);
+    let weight: Vec<f64> = influence(&st)
+        .iter()
+        .map(|&l| l as f64 + random(&mut state))
+        .collect();
+    let mut splitting: Vec<Point> = weight
+        .iter()
+        .map(|&w| if w < 1.0 { Point::Fine } else { Point::Undecided })
+        .collect();
+    let mut undecided: Vec<usize> = (0..n).filter(|&i| splitting[i] == Point::Undecided).collect();
+    while !undecided.is_empty() {
+        let coarse: Vec<usize> = undecided
+            .iter()
+            .cloned()
+            .filter(|&i| {
+                s.row(i)
+                    .col_indices()
+                    .iter()
+                    .chain(st.row(i).col_indices())
+                    .all(|&j| j == i || splitting[j] != Point::Undecided || weight[j] < weight[i])
+            })
+            .collect();
+        for &i in &coarse {
+            splitting[i] = Point::Coarse;
+        }
+        for &i in &coarse {
+            for &j in st.row(i).col_indices() {
+                if splitting[j] == Point::Undecided {
+                    splitting[j] = Point::Fine;
+                }
+            }
+        }
+        undecided.retain(|&i| splitting[i] == Point::Undecided);
+    }
+    splitting
+}
@@ -0,0 +1,90 @@
+//! Strength of connection: the couplings of the matrix the coarsening follows.
+//!
+//! The strength matrix `S` has the pattern of the strong off-diagonal couplings of
+//! `A` and keeps their values; row `i` lists the points `i` strongly depends on and
+//! row `i` of `S^T` the points strongly depending on `i`.
+
+use nalgebra_sparse::csr::CsrMatrix;
+
+/// Classical (Ruge–Stüben) strength: `j != i` is a strong connection of `i` when
+/// `-a_ij >= theta * max_{k != i} (-a_ik)`; rows without negative off-diagonal
+/// couplings have no strong connections.
+pub fn classical_strength(a: &CsrMatrix<f64>, theta: f64) -> CsrMatrix<f64> {
+    let n = a.nrows();
+    let mut offsets = Vec::with_capacity(n + 1);
+    let mut cols = Vec::new();
+    let mut vals = Vec::new();
+    offsets.push(0);
+    for (i, row) in a.row_iter().enumerate() {
+        let off_diagonal = || {
+            row.col_indices()
+                .iter()
+                .zip(row.values())
+                .filter(move |&(&j, _)| j != i)
+        };
+        let max = off_diagonal().fold(0.0f64, |m, (_, &v)| m.max(-v));
+        if max > 0.0 {
+            for (&j, &v) in off_diagonal() {
+                if -v >= theta * max {
+                    cols.push(j);
+                    vals.push(v);
+                }
+            }
+        }
+        offsets.push(cols.len());
+    }
+    CsrMatrix::try_from_csr_data(n, a.ncols(), offsets, cols, vals).unwrap()
+}
+
+/// The node matrix of a system with `block_size` unknowns per node: the Frobenius
+/// norms of the blocks, `a` itself for scalar problems.
+pub fn collapse(a: &CsrMatrix<f64>, block_size: usize) -> CsrMatrix<f64> {
+    if block_size == 1 {
+        return a.clone();
+    }
+    let n = a.nrows() / block_size;
+    let mut offsets = Vec::with_capacity(n + 1);
+    let mut cols = Vec::new();
+    let mut vals = Vec::new();
+    // position of node column J in the current node row
+    let mut slot = vec![usize::MAX; a.ncols() / block_size];
+    offsets.push(0);
+    for node in 0..n {
+        let start = cols.len();
+        for i in node * block_size..(node + 1) * block_size {
+            let row = a.row(i);
+            for (&j, &v) in row.col_indices().iter().zip(row.values()) {
+                let jn = j / block_size;
+                if slot[jn] == usize::MAX {
+                    slot[jn] = cols.len();
+                    cols.push(jn);
+                    vals.push(0.0);
+                }
+                vals[slot[jn]] += v * v;
+            }
+        }
+        let mut entries: Vec<(usize, f64)> = cols[start..]
+            .iter()
+            .zip(&vals[start..])
+            .map(|(&j, &v)| (j, v.sqrt()))
+            .collect();
+        entries.sort_by_key(|&(j, _)| j);
+        for (k, (j, v)) in entries.into_iter().enumerate() {
+            slot[j] = usize::MAX;
+            cols[start + k] = j;
+            vals[start + k] = v;
+        }
+        offsets.push(cols.len());
+    }
+    CsrMatrix::try_from_csr_data(n, a.ncols() / block_size, offsets, cols, vals).unwrap()
+}
+
+/// Symmetric strength of the node graph: `j != i` is strongly connected to `i` when
+/// `|a_ij| >= theta * sqrt(|a_ii a_jj|)`.
+pub fn symmetric_strength(a: &CsrMatrix<f64>, theta: f64, block_size: usize) -> CsrMatrix<f64> {
+    let nodes = collapse(a, block_size);
+    let diagonal: Vec<f64> = (0..nodes.nrows())
+        .map(|i| nodes.get_entry(i, i).map_or(0.0, |e| e.into_value()).abs())
+        .collect();
+    nodes.filter(|i, j, &v| i != j && v.abs() >= theta * (diagonal[i] * diagonal[j]).sqrt())
+}
@@ -0,0 +1,71 @@
+//! Model problems for testing the solvers.
+
+use nalgebra_sparse::{coo::CooMatrix, csr::CsrMatrix};
+
+/// The 3-point finite difference Laplacian `tridiag(-1, 2, -1)` of order `n`.
+pub fn poisson_1d(n: usize) -> CsrMatrix<f64> {
+    let mut coo = CooMatrix::new(n, n);
+    for i in 0..n {
+        if i > 0 {
+            coo.push(i, i - 1, -1.0);
+        }
+        coo.push(i, i, 2.0);
+        if i + 1 < n {
+            coo.push(i, i + 1, -1.0);
+        }
+    }
+    CsrMatrix::from(&coo)
+}
+
+/// The 5-point Laplacian on an `nx` x `ny` grid with Dirichlet boundaries, unknown
+/// `(i, j)` numbered `i + nx * j`; `epsilon` scales the couplings along y
+/// (1 for the isotropic problem).
+pub fn poisson_2d(nx: usize, ny: usize, epsilon: f64) -> CsrMatrix<f64> {
+    let n = nx * ny;
+    let mut coo = CooMatrix::new(n, n);
+    for j in 0..ny {
+        for i in 0..nx {
+            let k = i + nx * j;
+            if j > 0 {
+                coo.push(k, k - nx, -epsilon);
+            }
+            if i > 0 {
+                coo.push(k, k - 1, -1.0);
+            }
+            coo.push(k, k, 2.0 + 2.0 * epsilon);
+            if i + 1 < nx {
+                coo.push(k, k + 1, -1.0);
+            }
+            if j + 1 < ny {
+                coo.push(k, k + nx, -epsilon);
+            }
+        }
+    }
+    CsrMatrix::from(&coo)
+}
+
+/// `poisson_2d(nx, ny, 1)` plus the first order upwind discretization of the convection
+/// `peclet * (d/dx + d/dy)` scaled by the mesh size, nonsymmetric for `peclet != 0`.
+pub fn convection_diffusion_2d(nx: usize, ny: usize, peclet: f64) -> CsrMatrix<f64> {
+    let n = nx * ny;
+    let mut coo = CooMatrix::new(n, n);
+    for j in 0..ny {
+        for i in 0..nx {
+            let k = i + nx * j;
+            if j > 0 {
+                coo.push(k, k - nx, -1.0 - peclet);
+            }
+            if i > 0 {
+                coo.push(k, k - 1, -1.0 - peclet);
+            }
+            coo.push(k, k, 4.0 + 2.0 * peclet);
+            if i + 1 < nx {
+                coo.push(k, k + 1, -1.0);
+            }
+            if j + 1 < ny {
+                coo.push(k, k + nx, -1.0);
+            }
+        }
+    }
+    CsrMatrix::from(&coo)
+}
@@ -1,5 +1,19 @@
+//! The MatrixMarket exchange format, <https://math.nist.gov/MatrixMarket/formats.html>.
+//!
+//! A banner `%%MatrixMarket matrix <format> <field> <symmetry>` (case insensitive),
+//! comment lines starting with `%`, the size line and the entries:
+//! - `coordinate`: `rows cols entries`, then `i j [value]` per entry, 1-based;
+//! - `array`: `rows cols`, then the values column by column, only the lower triangle
+//!   of symmetric and hermitian matrices and the strictly lower one of skew-symmetric ones.
+//!
+//! Fields are `real` (or `double`), `integer`, `complex` (real and imaginary part) and
+//! `pattern` (no value, coordinate only); symmetries `general`, `symmetric`,
+//! `skew-symmetric` (not for patterns) and `hermitian` (complex only).
+//! The reader expands the symmetries: `a_ji` is `a_ij`, `-a_ij` or `conj(a_ij)`.
+
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::Read;
+use std::str::FromStr;
 use num_complex::Complex64;
 use nalgebra_sparse::{coo::CooMatrix, csr::CsrMatrix};
 
@@ -8,6 +22,15 @@ enum DataType {
     Real(Vec<f64>),
     Complex(Vec<Complex64>),
     Integer(Vec<isize>),
+    Pattern,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Symmetry {
+    General,
+    Symmetric,
+    SkewSymmetric,
+    Hermitian,
 }
 
 pub struct MatrixMarketReader {
@@ -18,116 +41,206 @@ pub struct MatrixMarketReader {
     data: DataType,
 }
 
-fn filename_to_string(s: &str) -> io::Result<String> {
-    let mut file = File::open(s).expect("file not found");
+fn filename_to_string(s: &str) -> Result<String, String> {
+    let mut file = File::open(s).map_err(|e| e.to_string())?;
     let mut s = String::new();
-    file.read_to_string(&mut s)?;
+    file.read_to_string(&mut s).map_err(|e| e.to_string())?;
     Ok(s)
 }
 
-fn words_by_line<'a>(s: &'a str) -> Vec<Vec<&'a str>> {
-    s.lines().map(|line| { line.split_whitespace().collect() }).collect()
+fn parse<T: FromStr>(word: &str, line: usize) -> Result<T, String> {
+    word.parse::<T>().map_err(|_| format!("line {}: cannot parse `{}`", line, word))
+}
+
+impl DataType {
+    // the number of words of a value
+    fn width(&self) -> usize {
+        match self {
+            DataType::Complex(_) => 2,
+            DataType::Pattern => 0,
+            _ => 1,
+        }
+    }
+
+    fn push(&mut self, words: &[&str], line: usize) -> Result<(), String> {
+        match self {
+            DataType::Real(v) => v.push(parse(words[0], line)?),
+            DataType::Integer(v) => v.push(parse(words[0], line)?),
+            DataType::Complex(v) => v.push(Complex64::new(parse(words[0], line)?, parse(words[1], line)?)),
+            DataType::Pattern => {}
+        }
+        Ok(())
+    }
+
+    // appends the mirror of value `k` under `symmetry`
+    fn push_mirror(&mut self, k: usize, symmetry: Symmetry) {
+        match (self, symmetry) {
+            (DataType::Real(v), Symmetry::SkewSymmetric) => v.push(-v[k]),
+            (DataType::Real(v), _) => v.push(v[k]),
+            (DataType::Integer(v), Symmetry::SkewSymmetric) => v.push(-v[k]),
+            (DataType::Integer(v), _) => v.push(v[k]),
+            (DataType::Complex(v), Symmetry::SkewSymmetric) => v.push(-v[k]),
+            (DataType::Complex(v), Symmetry::Hermitian) => v.push(v[k].conj()),
+            (DataType::Complex(v), _) => v.push(v[k]),
+            (DataType::Pattern, _) => {}
+        }
+    }
 }
 
 impl MatrixMarketReader {
     pub fn new(fname: &str) -> Result<Self, String> {
-        let whole_file = filename_to_string(fname).unwrap();
-        let wbyl = &words_by_line(&whole_file);
-        let mm_header = &wbyl[0];
-        let (banner, mtx, coord, dtype, storage) = (mm_header[0],
-                                                    mm_header[1],
-                                                    mm_header[2],
-                                                    mm_header[3],
-                                                    mm_header[4]);
-        if banner.ne("%%MatrixMarket") { return Err(String::from("no banner")); }
-        if mtx.ne("matrix") { return Err(String::from("not a matrix")); }
-        let is_symmetric = match storage {
-                             "symmetric" => true,
-                             "general"   => false,
-                              _          => return Err(String::from("unsupported storage type"))};
-        let is_sparse = match coord {
-                          "coordinate"   => true,
-                          "array"        => false,
-                           _             => return Err(String::from("unsupported coordinate type"))};
-        let mut data : DataType = match dtype {
-                           "real"        => DataType::Real(Vec::new()),
-                           "complex"     => DataType::Complex(Vec::new()),
-                           "integer"     => DataType::Integer(Vec::new()),
-                           _             => return Err(String::from("unsupported data type"))};
-
-        let mut nrows = 0usize;
-        let mut ncols = 0usize;
-        let mut nnz  = 0usize;
-        let mut row = Vec::<usize>::new();
-        let mut col = Vec::<usize>::new();
-        for words in wbyl {
-            if words[0].starts_with('%') { // skip comments that starts with %
-                //println!("words: {:?}", words);
-            } else {
-                // read triples, the 1st one contains (rows, cols, nnz) the others - (i, j, val)
-                macro_rules! parse_word {
-                    ($t:ty, $i:expr) => {
-                        words[$i].parse::<$t>().unwrap()
-                    };
-                    ($i:expr) => { // `$t` defaulted to `i32`
-                        parse_word!(usize, $i)
-                    };
-                }
-                let reserve_data_vals = move |data: DataType, nnz: usize| match data {
-                    DataType::Real(mut v)    => { v.reserve(nnz); DataType::Real(v)},
-                    DataType::Integer(mut v) => { v.reserve(nnz); DataType::Integer(v)},
-                    DataType::Complex(mut v) => { v.reserve(nnz); DataType::Complex(v)},
-                };
-                let parse_data_vals = move |data: DataType, i: usize| match data {
-                    DataType::Real(mut v)    => { v.push(parse_word!(f64,   i));
-                                                  DataType::Real(v)},
-                    DataType::Integer(mut v) => { v.push(parse_word!(isize, i));
-                                                  DataType::Integer(v)},
-                    DataType::Complex(mut v) => { v.push(Complex64::new(parse_word!(f64, i),
-                                                                        parse_word!(f64, i + 1)));
-                                                  DataType::Complex(v)},
-                };
-                if nrows == 0 {
-                    nrows = parse_word!(0);
-                    ncols = parse_word!(1);
-                    nnz  = parse_word!(2);
-                    if is_symmetric { nnz *= 2; }
-                    row.reserve(nnz);
-                    col.reserve(nnz);
-                    data = reserve_data_vals(data, nnz);
-                } else {
-                    let x = parse_word!(0) - 1;
-                    let y = parse_word!(1) - 1;
-                    row.push(x);
-                    col.push(y);
-                    data = parse_data_vals(data, 2);
-                    //println!("x: {} y: {} v: {:10.4e}", x, y, v);
+        filename_to_string(fname)?.parse()
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.nrows
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    /// The number of entries, the symmetric counterparts included.
+    pub fn nnz(&self) -> usize {
+        self.row.len()
+    }
+
+    pub fn is_complex(&self) -> bool {
+        matches!(self.data, DataType::Complex(_))
+    }
+}
+
+/// Reads the content of a MatrixMarket file.
+impl FromStr for MatrixMarketReader {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(k, line)| (k + 1, line.split_whitespace().collect::<Vec<&str>>()));
+        let header: Vec<String> = match lines.next() {
+            Some((_, words)) => words.iter().map(|w| w.to_lowercase()).collect(),
+            None => return Err(String::from("empty file")),
+        };
+        if header.len() != 5 || header[0] != "%%matrixmarket" {
+            return Err(String::from("no banner"));
+        }
+        if header[1] != "matrix" {
+            return Err(String::from("not a matrix"));
+        }
+        let is_sparse = match header[2].as_str() {
+            "coordinate" => true,
+            "array" => false,
+            _ => return Err(format!("unsupported format `{}`", header[2])),
+        };
+        let mut data = match header[3].as_str() {
+            "real" | "double" => DataType::Real(Vec::new()),
+            "complex" => DataType::Complex(Vec::new()),
+            "integer" => DataType::Integer(Vec::new()),
+            "pattern" if is_sparse => DataType::Pattern,
+            _ => return Err(format!("unsupported field `{}` of the {} format", header[3], header[2])),
+        };
+        let symmetry = match header[4].as_str() {
+            "general" => Symmetry::General,
+            "symmetric" => Symmetry::Symmetric,
+            "skew-symmetric" if !matches!(data, DataType::Pattern) => Symmetry::SkewSymmetric,
+            "hermitian" if matches!(data, DataType::Complex(_)) => Symmetry::Hermitian,
+            _ => return Err(format!("unsupported symmetry `{}` of a {} matrix", header[4], header[3])),
+        };
+
+        // skip the comments and blank lines
+        let mut lines = lines.filter(|(_, words)| !words.is_empty() && !words[0].starts_with('%'));
+        let (line, size) = lines.next().ok_or_else(|| String::from("no size line"))?;
+        if size.len() != if is_sparse { 3 } else { 2 } {
+            return Err(format!("line {}: bad size line", line));
+        }
+        let nrows: usize = parse(size[0], line)?;
+        let ncols: usize = parse(size[1], line)?;
+        if symmetry != Symmetry::General && nrows != ncols {
+            return Err(format!("a {:?} matrix of {} x {}", symmetry, nrows, ncols));
+        }
+        // the (row, column) of the stored entries, given by the lines of coordinate files
+        let positions: Box<dyn Iterator<Item = (usize, usize)>> = if is_sparse {
+            let entries: usize = parse(size[2], line)?;
+            Box::new(std::iter::repeat_n((usize::MAX, usize::MAX), entries))
+        } else {
+            let first_row = move |j: usize| match symmetry {
+                Symmetry::General => 0,
+                Symmetry::SkewSymmetric => j + 1,
+                _ => j,
+            };
+            Box::new((0..ncols).flat_map(move |j| (first_row(j)..nrows).map(move |i| (i, j))))
+        };
+
+        let width = if is_sparse { 2 } else { 0 } + data.width();
+        let mut row = Vec::new();
+        let mut col = Vec::new();
+        for (i, j) in positions {
+            let (line, words) = lines.next().ok_or_else(|| String::from("missing entries"))?;
+            if words.len() != width {
+                return Err(format!("line {}: expected {} words", line, width));
+            }
+            let (i, j) = if is_sparse {
+                let (i, j): (usize, usize) = (parse(words[0], line)?, parse(words[1], line)?);
+                if i == 0 || i > nrows || j == 0 || j > ncols {
+                    return Err(format!("line {}: entry ({}, {}) out of {} x {}", line, i, j, nrows, ncols));
                 }
+                (i - 1, j - 1)
+            } else {
+                (i, j)
+            };
+            if symmetry == Symmetry::SkewSymmetric && i == j {
+                return Err(format!("line {}: diagonal entry of a skew-symmetric matrix", line));
             }
-            //println!("words: {:?}", words);
+            data.push(&words[width - data.width()..], line)?;
+            row.push(i);
+            col.push(j);
+        }
+        if let Some((line, _)) = lines.next() {
+            return Err(format!("line {}: more entries than declared", line));
         }
 
-        println!("wbyl[0]: {:?}", mm_header);
-        //println!("data: {:?}", data);
-        Ok(Self { nrows: nrows, ncols: ncols, row: row, col: col, data: data, })
+        if symmetry != Symmetry::General {
+            for k in 0..row.len() {
+                if row[k] != col[k] {
+                    row.push(col[k]);
+                    col.push(row[k]);
+                    data.push_mirror(k, symmetry);
+                }
+            }
+        }
+        Ok(Self { nrows, ncols, row, col, data })
     }
 }
 
-pub fn create_csr(mm: &MatrixMarketReader) -> CsrMatrix<f64>
+fn coo<T>(mm: &MatrixMarketReader, values: Vec<T>) -> CooMatrix<T>
+where
+    T: nalgebra::Scalar,
 {
-    let coo = match &mm.data {
-       DataType::Real(v) => CooMatrix::try_from_triplets(mm.nrows, mm.ncols, mm.row.clone(), mm.col.clone(), v.to_vec()),
-       _ => unimplemented!(),
-       //DataType::Integer(v) => CooMatrix::try_from_triplets(mm.nrows, mm.ncols, mm.row.clone(), mm.col.clone(), v.to_vec()),
-       //DataType::Complex(v) => CooMatrix::try_from_triplets(mm.nrows, mm.ncols, mm.row.clone(), mm.col.clone(), v.to_vec()),
+    CooMatrix::try_from_triplets(mm.nrows, mm.ncols, mm.row.clone(), mm.col.clone(), values).unwrap()
+}
+
+/// The real matrix, pattern entries one; duplicate entries are summed.
+/// Panics on complex matrices, see `create_complex_csr`.
+pub fn create_csr(mm: &MatrixMarketReader) -> CsrMatrix<f64> {
+    let values = match &mm.data {
+        DataType::Real(v) => v.clone(),
+        DataType::Integer(v) => v.iter().map(|&x| x as f64).collect(),
+        DataType::Pattern => vec![1.0; mm.nnz()],
+        DataType::Complex(_) => panic!("create_csr() of a complex matrix"),
     };
-    //println!("m.row : {:?}", &mm.row);
-    //println!("coo : {:?}", &coo);
-    let csr = CsrMatrix::from(&coo.unwrap());
-    csr
+    CsrMatrix::from(&coo(mm, values))
 }
 
-fn use_it(fname: &str) {
-    let mm = MatrixMarketReader::new("xxx");
+/// The matrix of any field as a complex one.
+pub fn create_complex_csr(mm: &MatrixMarketReader) -> CsrMatrix<Complex64> {
+    match &mm.data {
+        DataType::Complex(v) => CsrMatrix::from(&coo(mm, v.clone())),
+        _ => {
+            let real = create_csr(mm);
+            let values = real.values().iter().map(|&x| Complex64::new(x, 0.0)).collect();
+            CsrMatrix::try_from_pattern_and_values(real.pattern().clone(), values).unwrap()
+        }
+    }
 }
- 
@@ -0,0 +1,4 @@
+pub use self::preconditioner::{Identity, Ilu0, Jacobi, Preconditioner};
+pub use self::solvers::{bicgstab, cg, gmres, solve, Convergence, Krylov, KrylovParams};
+pub mod preconditioner;
+pub mod solvers;
@@ -0,0 +1,113 @@
+//! Preconditioners: approximate inverses `M^-1` of the system matrix.
+
+use nalgebra_sparse::csr::CsrMatrix;
+use crate::amg::Multigrid;
+use crate::vector::diagonal;
+
+pub trait Preconditioner {
+    /// `z = M^-1 r`.
+    fn apply(&self, r: &[f64], z: &mut [f64]);
+}
+
+/// No preconditioning, `M = I`.
+pub struct Identity;
+
+impl Preconditioner for Identity {
+    fn apply(&self, r: &[f64], z: &mut [f64]) {
+        z.copy_from_slice(r);
+    }
+}
+
+/// The diagonal, `M = D`; zero rows are left alone.
+pub struct Jacobi {
+    dinv: Vec<f64>,
+}
+
+impl Jacobi {
+    pub fn new(a: &CsrMatrix<f64>) -> Self {
+        let dinv = diagonal(a).into_iter().map(|d| if d != 0.0 { 1.0 / d } else { 1.0 }).collect();
+        Jacobi { dinv }
+    }
+}
+
+impl Preconditioner for Jacobi {
+    fn apply(&self, r: &[f64], z: &mut [f64]) {
+        for ((zi, ri), d) in z.iter_mut().zip(r).zip(&self.dinv) {
+            *zi = d * ri;
+        }
+    }
+}
+
+/// Incomplete LU factorization without fill-in, `M = L U` with `L` unit lower and
+/// `U` upper triangular on the pattern of `A`, stored together in it.
+pub struct Ilu0 {
+    offsets: Vec<usize>,
+    cols: Vec<usize>,
+    vals: Vec<f64>,
+    /// position of the diagonal entry of each row
+    diagonal: Vec<usize>,
+}
+
+impl Ilu0 {
+    /// Fails on a missing or zero pivot.
+    pub fn new(a: &CsrMatrix<f64>) -> Result<Self, String> {
+        let n = a.nrows();
+        let (offsets, cols, vals) = a.csr_data();
+        let (offsets, cols, mut vals) = (offsets.to_vec(), cols.to_vec(), vals.to_vec());
+        let mut diagonal = Vec::with_capacity(n);
+        // position of column j in the current row
+        let mut slot = vec![usize::MAX; n];
+        for i in 0..n {
+            let row = offsets[i]..offsets[i + 1];
+            for p in row.clone() {
+                slot[cols[p]] = p;
+            }
+            for p in row.clone() {
+                let k = cols[p];
+                if k >= i {
+                    break;
+                }
+                vals[p] /= vals[diagonal[k]];
+                let factor = vals[p];
+                for q in diagonal[k] + 1..offsets[k + 1] {
+                    if slot[cols[q]] != usize::MAX {
+                        vals[slot[cols[q]]] -= factor * vals[q];
+                    }
+                }
+            }
+            match row.clone().find(|&p| cols[p] == i) {
+                Some(p) if vals[p] != 0.0 => diagonal.push(p),
+                _ => return Err(format!("ILU(0): zero pivot in row {}", i)),
+            }
+            for p in row {
+                slot[cols[p]] = usize::MAX;
+            }
+        }
+        Ok(Ilu0 { offsets, cols, vals, diagonal })
+    }
+}
+
+impl Preconditioner for Ilu0 {
+    fn apply(&self, r: &[f64], z: &mut [f64]) {
+        let n = r.len();
+        for i in 0..n {
+            let lower = self.offsets[i]..self.diagonal[i];
+            z[i] = r[i] - lower.map(|p| self.vals[p] * z[self.cols[p]]).sum::<f64>();
+        }
+        for i in (0..n).rev() {
+            let upper = self.diagonal[i] + 1..self.offsets[i + 1];
+            let s: f64 = upper.map(|p| self.vals[p] * z[self.cols[p]]).sum();
+            z[i] = (z[i] - s) / self.vals[self.diagonal[i]];
+        }
+    }
+}
+
+/// One multigrid cycle from a zero initial guess; symmetric for symmetric `A` with
+/// symmetric smoothing (Jacobi, l1-Jacobi, Chebyshev or symmetric Gauss–Seidel with
+/// as many pre as post sweeps).
+impl Preconditioner for Multigrid {
+    fn apply(&self, r: &[f64], z: &mut [f64]) {
+        z.iter_mut().for_each(|v| *v = 0.0);
+        self.cycle(r, z);
+    }
+}
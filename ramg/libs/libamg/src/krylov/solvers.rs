@@ -0,0 +1,254 @@
+//! Preconditioned Krylov subspace methods.
+//!
+//! All of them stop once the norm of the residual `b - A x` drops below the tolerance
+//! relative to `b` (to the initial residual for `b = 0`) and record it every iteration;
+//! BiCGStab and GMRES are preconditioned from the right so that this is the residual
+//! they minimize or update, not the preconditioned one.
+
+use std::str::FromStr;
+use nalgebra_sparse::csr::CsrMatrix;
+use super::preconditioner::Preconditioner;
+use crate::vector::{axpy, dot, norm, residual, spmv};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Krylov {
+    /// conjugate gradients, symmetric positive definite `A` and `M`
+    Cg,
+    BiCgStab,
+    /// restarted GMRES
+    Gmres,
+}
+
+impl FromStr for Krylov {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "cg" => Ok(Krylov::Cg),
+            "bicgstab" => Ok(Krylov::BiCgStab),
+            "gmres" => Ok(Krylov::Gmres),
+            _ => Err(format!("unknown Krylov method `{}`, expected cg|bicgstab|gmres", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KrylovParams {
+    pub max_iterations: usize,
+    /// on the residual relative to the right hand side
+    pub tolerance: f64,
+    /// GMRES restart length
+    pub restart: usize,
+}
+
+impl Default for KrylovParams {
+    fn default() -> Self {
+        KrylovParams { max_iterations: 500, tolerance: 1e-8, restart: 30 }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Convergence {
+    pub converged: bool,
+    pub iterations: usize,
+    /// residual norms, the initial one and one per iteration
+    pub history: Vec<f64>,
+}
+
+// the residual norm to stop at and the record of the initial one
+fn start(a: &CsrMatrix<f64>, b: &[f64], x: &[f64], r: &mut [f64], params: &KrylovParams) -> (f64, Convergence) {
+    residual(a, b, x, r);
+    let r0 = norm(r);
+    let reference = if norm(b) > 0.0 { norm(b) } else { r0 };
+    let convergence = Convergence { converged: r0 <= params.tolerance * reference, iterations: 0, history: vec![r0] };
+    (params.tolerance * reference, convergence)
+}
+
+impl Convergence {
+    // records an iteration, true when done
+    fn step(&mut self, residual: f64, target: f64, params: &KrylovParams) -> bool {
+        self.iterations += 1;
+        self.history.push(residual);
+        self.converged = residual <= target;
+        self.converged || self.iterations >= params.max_iterations
+    }
+}
+
+pub fn solve(
+    method: Krylov,
+    a: &CsrMatrix<f64>,
+    b: &[f64],
+    x: &mut [f64],
+    m: &dyn Preconditioner,
+    params: &KrylovParams,
+) -> Convergence {
+    match method {
+        Krylov::Cg => cg(a, b, x, m, params),
+        Krylov::BiCgStab => bicgstab(a, b, x, m, params),
+        Krylov::Gmres => gmres(a, b, x, m, params),
+    }
+}
+
+/// Preconditioned conjugate gradients.
+pub fn cg(a: &CsrMatrix<f64>, b: &[f64], x: &mut [f64], m: &dyn Preconditioner, params: &KrylovParams) -> Convergence {
+    let n = x.len();
+    let mut r = vec![0.0; n];
+    let (target, mut convergence) = start(a, b, x, &mut r, params);
+    if convergence.converged || params.max_iterations == 0 {
+        return convergence;
+    }
+    let mut z = vec![0.0; n];
+    m.apply(&r, &mut z);
+    let mut p = z.clone();
+    let mut q = vec![0.0; n];
+    let mut rz = dot(&r, &z);
+    loop {
+        spmv(a, &p, &mut q);
+        let pq = dot(&p, &q);
+        if pq == 0.0 {
+            break;
+        }
+        let alpha = rz / pq;
+        axpy(alpha, &p, x);
+        axpy(-alpha, &q, &mut r);
+        if convergence.step(norm(&r), target, params) {
+            break;
+        }
+        m.apply(&r, &mut z);
+        let rz_new = dot(&r, &z);
+        let beta = rz_new / rz;
+        rz = rz_new;
+        for (pi, zi) in p.iter_mut().zip(&z) {
+            *pi = zi + beta * *pi;
+        }
+    }
+    convergence
+}
+
+/// Right preconditioned BiCGStab; stops early on a breakdown, `r_0^T r = 0` or
+/// `r_0^T A M^-1 p = 0`.
+pub fn bicgstab(a: &CsrMatrix<f64>, b: &[f64], x: &mut [f64], m: &dyn Preconditioner, params: &KrylovParams) -> Convergence {
+    let n = x.len();
+    let mut r = vec![0.0; n];
+    let (target, mut convergence) = start(a, b, x, &mut r, params);
+    if convergence.converged || params.max_iterations == 0 {
+        return convergence;
+    }
+    let shadow = r.clone();
+    let (mut rho, mut alpha, mut omega) = (1.0, 1.0, 1.0);
+    let (mut p, mut v) = (vec![0.0; n], vec![0.0; n]);
+    let (mut phat, mut shat, mut t) = (vec![0.0; n], vec![0.0; n], vec![0.0; n]);
+    loop {
+        let rho_new = dot(&shadow, &r);
+        if rho_new == 0.0 || omega == 0.0 {
+            break;
+        }
+        let beta = rho_new / rho * alpha / omega;
+        rho = rho_new;
+        for ((pi, ri), vi) in p.iter_mut().zip(&r).zip(&v) {
+            *pi = ri + beta * (*pi - omega * vi);
+        }
+        m.apply(&p, &mut phat);
+        spmv(a, &phat, &mut v);
+        let shadow_v = dot(&shadow, &v);
+        if shadow_v == 0.0 {
+            break;
+        }
+        alpha = rho / shadow_v;
+        // r becomes s = r - alpha v
+        axpy(-alpha, &v, &mut r);
+        axpy(alpha, &phat, x);
+        let s_norm = norm(&r);
+        if s_norm <= target {
+            convergence.step(s_norm, target, params);
+            break;
+        }
+        m.apply(&r, &mut shat);
+        spmv(a, &shat, &mut t);
+        let tt = dot(&t, &t);
+        omega = if tt > 0.0 { dot(&t, &r) / tt } else { 0.0 };
+        axpy(omega, &shat, x);
+        axpy(-omega, &t, &mut r);
+        if convergence.step(norm(&r), target, params) {
+            break;
+        }
+    }
+    convergence
+}
+
+/// Restarted right preconditioned GMRES (flexible: the preconditioned basis is kept,
+/// so the preconditioner may change between iterations). The residual norms recorded
+/// are the ones minimized in the Krylov subspace, the true one at restarts.
+pub fn gmres(a: &CsrMatrix<f64>, b: &[f64], x: &mut [f64], m: &dyn Preconditioner, params: &KrylovParams) -> Convergence {
+    let n = x.len();
+    let restart = params.restart.max(1);
+    let mut r = vec![0.0; n];
+    let (target, mut convergence) = start(a, b, x, &mut r, params);
+    let mut beta = convergence.history[0];
+    let mut w = vec![0.0; n];
+    while !convergence.converged && convergence.iterations < params.max_iterations {
+        // the Arnoldi basis `v`, its preconditioned images `z`, the Hessenberg matrix
+        // `h` by columns reduced by the Givens rotations `(c, s)` and the right hand side `g`
+        let mut v = vec![r.iter().map(|ri| ri / beta).collect::<Vec<f64>>()];
+        let mut z: Vec<Vec<f64>> = Vec::with_capacity(restart);
+        let mut h: Vec<Vec<f64>> = Vec::with_capacity(restart);
+        let (mut c, mut s): (Vec<f64>, Vec<f64>) = (Vec::with_capacity(restart), Vec::with_capacity(restart));
+        let mut g = vec![beta];
+        for j in 0..restart {
+            let mut zj = vec![0.0; n];
+            m.apply(&v[j], &mut zj);
+            spmv(a, &zj, &mut w);
+            z.push(zj);
+            let mut column = Vec::with_capacity(j + 2);
+            for vi in &v {
+                let hij = dot(&w, vi);
+                axpy(-hij, vi, &mut w);
+                column.push(hij);
+            }
+            let hnext = norm(&w);
+            column.push(hnext);
+            for i in 0..j {
+                let (upper, lower) = (column[i], column[i + 1]);
+                column[i] = c[i] * upper + s[i] * lower;
+                column[i + 1] = -s[i] * upper + c[i] * lower;
+            }
+            let d = column[j].hypot(column[j + 1]);
+            let (cj, sj) = if d > 0.0 { (column[j] / d, column[j + 1] / d) } else { (1.0, 0.0) };
+            column[j] = d;
+            column[j + 1] = 0.0;
+            g.push(-sj * g[j]);
+            g[j] *= cj;
+            c.push(cj);
+            s.push(sj);
+            h.push(column);
+            // happy breakdown: the solution is in the subspace
+            let breakdown = hnext == 0.0;
+            if !breakdown {
+                v.push(w.iter().map(|wi| wi / hnext).collect());
+            }
+            if convergence.step(g[j + 1].abs(), target, params) || breakdown {
+                break;
+            }
+        }
+        // x += Z y with H y = g
+        let k = h.len();
+        let mut y = vec![0.0; k];
+        for i in (0..k).rev() {
+            let sum: f64 = (i + 1..k).map(|l| h[l][i] * y[l]).sum();
+            y[i] = if h[i][i] != 0.0 { (g[i] - sum) / h[i][i] } else { 0.0 };
+        }
+        for (zi, yi) in z.iter().zip(&y) {
+            axpy(*yi, zi, x);
+        }
+        residual(a, b, x, &mut r);
+        beta = norm(&r);
+        if let Some(last) = convergence.history.last_mut() {
+            *last = beta;
+        }
+        convergence.converged = beta <= target;
+        if beta == 0.0 {
+            break;
+        }
+    }
+    convergence
+}
@@ -1,2 +1,5 @@
 pub mod io;
-
+pub mod amg;
+pub mod gallery;
+pub mod krylov;
+pub mod vector;
@@ -0,0 +1,37 @@
+//! Dense vector kernels of the solvers on `Vec<f64>`/slices.
+
+use nalgebra_sparse::csr::CsrMatrix;
+
+/// `y = A x`.
+pub fn spmv(a: &CsrMatrix<f64>, x: &[f64], y: &mut [f64]) {
+    for (yi, row) in y.iter_mut().zip(a.row_iter()) {
+        *yi = row.col_indices().iter().zip(row.values()).map(|(&j, &v)| v * x[j]).sum();
+    }
+}
+
+/// `r = b - A x`.
+pub fn residual(a: &CsrMatrix<f64>, b: &[f64], x: &[f64], r: &mut [f64]) {
+    for ((ri, &bi), row) in r.iter_mut().zip(b).zip(a.row_iter()) {
+        *ri = bi - row.col_indices().iter().zip(row.values()).map(|(&j, &v)| v * x[j]).sum::<f64>();
+    }
+}
+
+pub fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y).map(|(a, b)| a * b).sum()
+}
+
+pub fn norm(x: &[f64]) -> f64 {
+    dot(x, x).sqrt()
+}
+
+/// `y += alpha x`.
+pub fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+    for (yi, xi) in y.iter_mut().zip(x) {
+        *yi += alpha * xi;
+    }
+}
+
+/// The diagonal of `a`, zero where it is not stored.
+pub fn diagonal(a: &CsrMatrix<f64>) -> Vec<f64> {
+    (0..a.nrows()).map(|i| a.get_entry(i, i).map_or(0.0, |e| e.into_value())).collect()
+}
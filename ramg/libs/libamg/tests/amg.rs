@@ -0,0 +1,345 @@
+#[cfg(test)]
+mod tests {
+    use libamg::amg::aggregation::{constant_nullspace, smooth_prolongator, standard_aggregation, tentative_prolongator};
+    use libamg::amg::interpolation::{classical_interpolation, direct_interpolation};
+    use libamg::amg::splitting::{pmis, ruge_stuben};
+    use libamg::amg::strength::{classical_strength, symmetric_strength};
+    use libamg::amg::smoother::LevelSmoother;
+    use libamg::amg::{AmgParams, Coarsening, Cycle, Hierarchy, Interpolation, Method, Multigrid, Point, Smoother, SolveParams};
+    use libamg::gallery::{poisson_1d, poisson_2d};
+    use libamg::vector::{norm, residual};
+    use nalgebra::DMatrix;
+    use nalgebra_sparse::{coo::CooMatrix, csr::CsrMatrix};
+
+    fn row_sums(a: &CsrMatrix<f64>) -> Vec<f64> {
+        a.row_iter().map(|r| r.values().iter().sum()).collect()
+    }
+
+    // x^T A y
+    fn form(a: &CsrMatrix<f64>, x: &[f64], y: &[f64]) -> f64 {
+        a.triplet_iter().map(|(i, j, &v)| x[i] * v * y[j]).sum()
+    }
+
+    fn prolongate(p: &CsrMatrix<f64>, xc: &[f64]) -> Vec<f64> {
+        p.row_iter()
+            .map(|r| r.col_indices().iter().zip(r.values()).map(|(&j, &v)| v * xc[j]).sum())
+            .collect()
+    }
+
+    // `copies` interleaved uncoupled copies of `a`, node `i` holding unknowns `copies * i + c`
+    fn interleaved(a: &CsrMatrix<f64>, copies: usize) -> CsrMatrix<f64> {
+        let mut coo = CooMatrix::new(copies * a.nrows(), copies * a.ncols());
+        for (i, j, &v) in a.triplet_iter() {
+            for c in 0..copies {
+                coo.push(copies * i + c, copies * j + c, (c + 1) as f64 * v);
+            }
+        }
+        CsrMatrix::from(&coo)
+    }
+
+    #[test]
+    fn check_strength() {
+        let s = classical_strength(&poisson_1d(10), 0.25);
+        assert_eq!(s.nnz(), 18);
+        assert!(s.values().iter().all(|&v| v == -1.0));
+
+        // strongly coupled along x only
+        let a = poisson_2d(8, 8, 0.01);
+        let s = classical_strength(&a, 0.25);
+        assert!(s.triplet_iter().all(|(i, j, _)| i / 8 == j / 8));
+        assert!(classical_strength(&a, 0.001).nnz() == a.nnz() - 64);
+
+        let s = symmetric_strength(&a, 0.08, 1);
+        assert!(s.triplet_iter().all(|(i, j, _)| i / 8 == j / 8));
+        assert_eq!(s.nnz(), 2 * 7 * 8);
+        // the node graph of interleaved copies is the graph of one copy
+        let s2 = symmetric_strength(&interleaved(&a, 2), 0.08, 2);
+        assert_eq!(s2.pattern(), s.pattern());
+    }
+
+    #[test]
+    fn check_splitting() {
+        // every other point of the 1D problem is coarse
+        let s = classical_strength(&poisson_1d(9), 0.25);
+        let splitting = ruge_stuben(&s);
+        for (i, &p) in splitting.iter().enumerate() {
+            assert_eq!(p == Point::Coarse, i % 2 == 1, "{:?}", splitting);
+        }
+
+        let a = poisson_2d(20, 20, 1.0);
+        let s = classical_strength(&a, 0.25);
+        for splitting in [ruge_stuben(&s), pmis(&s, 3)] {
+            assert!(splitting.iter().all(|&p| p != Point::Undecided));
+            // every fine point strongly depends on a coarse point
+            for (i, &p) in splitting.iter().enumerate() {
+                if p == Point::Fine {
+                    assert!(s.row(i).col_indices().iter().any(|&j| splitting[j] == Point::Coarse));
+                }
+            }
+            let nc = splitting.iter().filter(|&&p| p == Point::Coarse).count();
+            assert!(nc > 400 / 8 && nc < 400 / 2 + 20, "{} coarse points", nc);
+        }
+
+        // PMIS coarse points are independent, reproducible for a seed
+        let splitting = pmis(&s, 3);
+        assert!(s.triplet_iter().all(|(i, j, _)| {
+            splitting[i] != Point::Coarse || splitting[j] != Point::Coarse
+        }));
+        assert_eq!(splitting, pmis(&s, 3));
+
+        // RS second pass: strongly connected fine points share a coarse point
+        let splitting = ruge_stuben(&s);
+        for (i, j, _) in s.triplet_iter() {
+            if splitting[i] == Point::Fine && splitting[j] == Point::Fine {
+                let ci: Vec<usize> = s.row(i).col_indices().to_vec();
+                assert!(
+                    s.row(j).col_indices().iter().any(|k| splitting[*k] == Point::Coarse && ci.contains(k)),
+                    "{} {}",
+                    i,
+                    j
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn check_interpolation() {
+        let a = poisson_2d(16, 16, 1.0);
+        let s = classical_strength(&a, 0.25);
+        let sums = row_sums(&a);
+        for splitting in [ruge_stuben(&s), pmis(&s, 7)] {
+            let nc = splitting.iter().filter(|&&p| p == Point::Coarse).count();
+            for p in [classical_interpolation(&a, &s, &splitting), direct_interpolation(&a, &s, &splitting)] {
+                assert_eq!((p.nrows(), p.ncols()), (256, nc));
+                assert!(p.values().iter().all(|&w| w > 0.0 && w <= 1.0 + 1e-12));
+                // constants are interpolated exactly away from the boundary
+                for (i, r) in p.row_iter().enumerate() {
+                    if sums[i] == 0.0 {
+                        let w: f64 = r.values().iter().sum();
+                        assert!((w - 1.0).abs() < 1e-12, "row {} sums to {}", i, w);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn check_aggregation() {
+        let a = poisson_2d(20, 20, 1.0);
+        let s = symmetric_strength(&a, 0.08, 1);
+        let (aggregate, count) = standard_aggregation(&s);
+        assert!(count > 400 / 9 && count < 400 / 3, "{} aggregates", count);
+        assert!(aggregate.iter().all(|&g| g < count));
+        // aggregates are nonempty and every member is strongly connected to another one
+        let mut sizes = vec![0; count];
+        for (i, &g) in aggregate.iter().enumerate() {
+            sizes[g] += 1;
+            assert!(sizes[g] == 1 || s.row(i).col_indices().iter().any(|&j| aggregate[j] == g) || {
+                (0..400).any(|j| j != i && aggregate[j] == g && s.row(j).col_indices().contains(&i))
+            });
+        }
+        assert!(sizes.iter().all(|&n| n > 1));
+
+        // isolated nodes stay out
+        let mut diagonal = CooMatrix::new(3, 3);
+        (0..3).for_each(|i| diagonal.push(i, i, 1.0));
+        let (aggregate, count) = standard_aggregation(&symmetric_strength(&CsrMatrix::from(&diagonal), 0.08, 1));
+        assert_eq!((aggregate, count), (vec![usize::MAX; 3], 0));
+    }
+
+    #[test]
+    fn check_tentative_prolongator() {
+        let b2 = interleaved(&poisson_2d(12, 12, 1.0), 2);
+        let s = symmetric_strength(&b2, 0.08, 2);
+        let (aggregate, count) = standard_aggregation(&s);
+        let b = constant_nullspace(b2.nrows(), 2);
+        let (t, bc) = tentative_prolongator(&aggregate, count, &b, 2);
+        assert_eq!((t.nrows(), t.ncols()), (288, 2 * count));
+        assert_eq!((bc.nrows(), bc.ncols()), (2 * count, 2));
+
+        // orthonormal columns, the near-nullspace interpolated exactly
+        let tt = &t.transpose() * &t;
+        for (i, j, &v) in tt.triplet_iter() {
+            assert!((v - if i == j { 1.0 } else { 0.0 }).abs() < 1e-12);
+        }
+        for c in 0..2 {
+            let column: Vec<f64> = bc.column(c).iter().cloned().collect();
+            let fine = prolongate(&t, &column);
+            assert!(fine.iter().enumerate().all(|(r, &v)| (v - b[(r, c)]).abs() < 1e-12));
+        }
+
+        // more near-nullspace vectors than unknowns in an aggregate: 1D with 1 and x
+        let a = poisson_1d(10);
+        let b = DMatrix::from_fn(10, 2, |i, c| if c == 0 { 1.0 } else { i as f64 });
+        let aggregate = vec![0, 0, 0, 1, 1, 1, 2, 2, 2, 3];
+        let (t, bc) = tentative_prolongator(&aggregate, 4, &b, 1);
+        for c in 0..2 {
+            let column: Vec<f64> = bc.column(c).iter().cloned().collect();
+            let fine = prolongate(&t, &column);
+            assert!(fine.iter().enumerate().all(|(r, &v)| (v - b[(r, c)]).abs() < 1e-12));
+        }
+        // the single point aggregate only carries the constant
+        assert_eq!(bc[(7, 1)], 0.0);
+        assert_eq!(t.get_entry(9, 7).unwrap().into_value(), 0.0);
+
+        // smoothing keeps the interior constants: A 1 = 0 away from the boundary
+        let (aggregate, count) = standard_aggregation(&symmetric_strength(&a, 0.08, 1));
+        let (t, bc) = tentative_prolongator(&aggregate, count, &constant_nullspace(10, 1), 1);
+        let p = smooth_prolongator(&a, &t, 4.0 / 3.0);
+        let column: Vec<f64> = bc.column(0).iter().cloned().collect();
+        let fine = prolongate(&p, &column);
+        assert!(fine[1..9].iter().all(|&v| (v - 1.0).abs() < 1e-12), "{:?}", fine);
+        assert!(fine[0] < 1.0 && fine[9] < 1.0);
+    }
+
+    #[test]
+    fn check_smoothed_aggregation() {
+        let a = poisson_2d(64, 64, 1.0);
+        let params = AmgParams::smoothed_aggregation();
+        assert_eq!(params.method, Method::SmoothedAggregation);
+        let h = Hierarchy::new(a.clone(), &params).unwrap();
+        let n = h.levels.len();
+        assert!(n > 2, "{} levels", n);
+        assert!(h.levels[n - 1].a.nrows() <= params.max_coarse);
+        assert!(h.levels[1].a.nrows() < a.nrows() / 4);
+        assert!(h.operator_complexity() > 1.0 && h.operator_complexity() < 1.6, "{}", h);
+
+        // two unknowns per node: coarse levels keep the two interleaved copies apart
+        let params = AmgParams { block_size: 2, ..AmgParams::smoothed_aggregation() };
+        let h2 = Hierarchy::new(interleaved(&a, 2), &params).unwrap();
+        assert_eq!(h2.levels[1].a.nrows(), 2 * h.levels[1].a.nrows());
+        for l2 in &h2.levels {
+            assert_eq!(l2.a.nrows() % 2, 0);
+            assert!(l2.a.triplet_iter().all(|(i, j, &v)| i % 2 == j % 2 || v == 0.0));
+        }
+
+        // a user near-nullspace of the wrong size
+        let b = DMatrix::from_element(10, 1, 1.0);
+        assert!(Hierarchy::with_nullspace(a.clone(), &params, Some(b)).is_err());
+        assert!(Hierarchy::new(poisson_1d(9), &params).is_err());
+        assert!("sa".parse::<Method>() == Ok(Method::SmoothedAggregation));
+    }
+
+    #[test]
+    fn check_hierarchy() {
+        let a = poisson_2d(64, 64, 1.0);
+        let x: Vec<f64> = (0..a.nrows()).map(|i| ((i * 7919) % 113) as f64 / 113.0 - 0.5).collect();
+        for &coarsening in [Coarsening::RugeStuben, Coarsening::Pmis].iter() {
+            for &interpolation in [Interpolation::Classical, Interpolation::Direct].iter() {
+                let params = AmgParams {
+                    coarsening,
+                    interpolation,
+                    ..AmgParams::default()
+                };
+                let h = Hierarchy::new(a.clone(), &params).unwrap();
+                let n = h.levels.len();
+                assert!(n > 3, "{} levels", n);
+                assert!(h.levels[n - 1].a.nrows() <= params.max_coarse);
+                assert!(h.levels[n - 1].p.is_none());
+                assert!(h.grid_complexity() > 1.0 && h.grid_complexity() < 2.0);
+                assert!(h.operator_complexity() > 1.0 && h.operator_complexity() < 4.0);
+
+                // Galerkin: x^T A_c y = (P x)^T A (P y), A_c symmetric
+                let level = &h.levels[0];
+                let p = level.p.as_ref().unwrap();
+                let ac = &h.levels[1].a;
+                let xc = &x[..ac.nrows()];
+                let yc: Vec<f64> = xc.iter().rev().cloned().collect();
+                let (px, py) = (prolongate(p, xc), prolongate(p, &yc));
+                let fine = form(&level.a, &px, &py);
+                assert!((form(ac, xc, &yc) - fine).abs() < 1e-10 * fine.abs().max(1.0));
+                assert!((form(ac, &yc, xc) - fine).abs() < 1e-10 * fine.abs().max(1.0));
+                let summary = format!("{}", h);
+                assert!(summary.contains("operator complexity"));
+            }
+        }
+
+        let params = AmgParams { max_levels: 2, ..AmgParams::default() };
+        assert_eq!(Hierarchy::new(a.clone(), &params).unwrap().levels.len(), 2);
+        assert!(Hierarchy::new(CsrMatrix::zeros(3, 4), &params).is_err());
+    }
+
+    const SMOOTHERS: [Smoother; 6] = [
+        Smoother::Jacobi,
+        Smoother::GaussSeidel,
+        Smoother::BackwardGaussSeidel,
+        Smoother::SymmetricGaussSeidel,
+        Smoother::L1Jacobi,
+        Smoother::Chebyshev,
+    ];
+
+    #[test]
+    fn check_smoothers() {
+        // the highest frequency error is damped, the lowest one hardly
+        let n = 63;
+        let a = poisson_1d(n);
+        let b = vec![0.0; n];
+        let mode = |k: usize| -> Vec<f64> {
+            (0..n).map(|i| (std::f64::consts::PI * (k * (i + 1)) as f64 / (n + 1) as f64).sin()).collect()
+        };
+        for &smoother in SMOOTHERS.iter() {
+            let s = LevelSmoother::new(&a, smoother, 2.0 / 3.0, 3);
+            let mut x = mode(n - 4);
+            s.smooth(&a, &b, &mut x, 3);
+            assert!(norm(&x) < 0.2 * norm(&mode(n - 4)), "{:?} {}", smoother, norm(&x));
+            let mut x = mode(1);
+            s.smooth(&a, &b, &mut x, 3);
+            assert!(norm(&x) > 0.9 * norm(&mode(1)), "{:?} {}", smoother, norm(&x));
+        }
+        // the smoothers leave the solution alone
+        let x0: Vec<f64> = (0..n).map(|i| i as f64).collect();
+        let mut b = vec![0.0; n];
+        residual(&a, &vec![0.0; n], &x0, &mut b);
+        b.iter_mut().for_each(|v| *v = -*v);
+        for &smoother in SMOOTHERS.iter() {
+            let mut x = x0.clone();
+            LevelSmoother::new(&a, smoother, 2.0 / 3.0, 3).smooth(&a, &b, &mut x, 2);
+            assert!(x.iter().zip(&x0).all(|(u, v)| (u - v).abs() < 1e-12), "{:?}", smoother);
+        }
+        assert_eq!("l1-jacobi".parse::<Smoother>(), Ok(Smoother::L1Jacobi));
+        assert!("sor".parse::<Smoother>().is_err());
+    }
+
+    #[test]
+    fn check_cycles() {
+        let a = poisson_2d(32, 32, 1.0);
+        let b: Vec<f64> = (0..a.nrows()).map(|i| ((i * 7919) % 113) as f64 / 113.0).collect();
+        for params in [AmgParams::default(), AmgParams::smoothed_aggregation()] {
+            let mut cycles = Vec::new();
+            for &cycle in [Cycle::V, Cycle::W, Cycle::F].iter() {
+                for &smoother in SMOOTHERS.iter() {
+                    let solve = SolveParams { cycle, smoother, ..SolveParams::default() };
+                    let mg = Multigrid::new(Hierarchy::new(a.clone(), &params).unwrap(), solve).unwrap();
+                    let mut x = vec![0.0; a.nrows()];
+                    let history = mg.solve(&b, &mut x);
+                    let last = history[history.len() - 1];
+                    assert!(last <= 1e-8 * norm(&b), "{:?} {:?} {:?}: {:?}", params.method, cycle, smoother, history);
+                    assert!(history.len() < 60, "{:?} {:?} {:?}: {:?}", params.method, cycle, smoother, history);
+                    // every cycle but the first one (Jacobi may start off worse) reduces the residual
+                    assert!(history.windows(2).skip(1).all(|w| w[1] < w[0]), "{:?} {:?} {:?}: {:?}", params.method, cycle, smoother, history);
+                    if smoother == Smoother::SymmetricGaussSeidel {
+                        cycles.push(history.len());
+                    }
+                }
+            }
+            // W- and F-cycles need no more cycles than V-cycles
+            assert!(cycles[1] <= cycles[0] && cycles[2] <= cycles[0], "{:?}", cycles);
+        }
+
+        // a single level is solved directly
+        let params = AmgParams { max_levels: 1, ..AmgParams::default() };
+        let mg = Multigrid::new(Hierarchy::new(poisson_2d(8, 8, 1.0), &params).unwrap(), SolveParams::default()).unwrap();
+        let mut x = vec![0.0; 64];
+        let history = mg.solve(&b[..64], &mut x);
+        assert_eq!(history.len(), 2);
+        assert!(history[1] < 1e-12);
+        // unless it is too large for dense LU
+        let solve = SolveParams { max_direct: 63, ..SolveParams::default() };
+        let error = Multigrid::new(Hierarchy::new(poisson_2d(8, 8, 1.0), &params).unwrap(), solve).err();
+        assert!(error.unwrap_or_default().contains("64 rows"));
+
+        let zero = CsrMatrix::from(&CooMatrix::new(4, 4));
+        assert!(Multigrid::new(Hierarchy::new(zero, &AmgParams::default()).unwrap(), SolveParams::default()).is_err());
+        assert_eq!("f".parse::<Cycle>(), Ok(Cycle::F));
+    }
+}
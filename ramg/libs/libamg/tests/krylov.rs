@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+    use libamg::amg::{AmgParams, Hierarchy, Multigrid, Smoother, SolveParams};
+    use libamg::gallery::{convection_diffusion_2d, poisson_1d, poisson_2d};
+    use libamg::krylov::{solve, Identity, Ilu0, Jacobi, Krylov, KrylovParams, Preconditioner};
+    use libamg::vector::{norm, residual};
+    use nalgebra_sparse::{coo::CooMatrix, csr::CsrMatrix};
+
+    fn rhs(n: usize) -> Vec<f64> {
+        (0..n).map(|i| ((i * 7919) % 113) as f64 / 113.0).collect()
+    }
+
+    fn true_residual(a: &CsrMatrix<f64>, b: &[f64], x: &[f64]) -> f64 {
+        let mut r = vec![0.0; x.len()];
+        residual(a, b, x, &mut r);
+        norm(&r)
+    }
+
+    fn multigrid(a: &CsrMatrix<f64>) -> Multigrid {
+        let hierarchy = Hierarchy::new(a.clone(), &AmgParams::default()).unwrap();
+        Multigrid::new(hierarchy, SolveParams::default()).unwrap()
+    }
+
+    #[test]
+    fn check_preconditioners() {
+        // ILU(0) of a tridiagonal matrix is its LU factorization
+        let a = poisson_1d(20);
+        let b = rhs(20);
+        let mut z = vec![0.0; 20];
+        Ilu0::new(&a).unwrap().apply(&b, &mut z);
+        assert!(true_residual(&a, &b, &z) < 1e-12);
+
+        let mut z = vec![0.0; 20];
+        Jacobi::new(&a).apply(&b, &mut z);
+        assert!(z.iter().zip(&b).all(|(zi, bi)| (zi - bi / 2.0).abs() < 1e-15));
+        Identity.apply(&b, &mut z);
+        assert_eq!(z, b);
+
+        // a zero pivot
+        let mut coo = CooMatrix::new(2, 2);
+        coo.push(0, 1, 1.0);
+        coo.push(1, 0, 1.0);
+        assert!(Ilu0::new(&CsrMatrix::from(&coo)).is_err());
+    }
+
+    #[test]
+    fn check_symmetric() {
+        let a = poisson_2d(32, 32, 1.0);
+        let b = rhs(a.nrows());
+        let params = KrylovParams::default();
+        let mg = multigrid(&a);
+        let jacobi = Jacobi::new(&a);
+        let ilu = Ilu0::new(&a).unwrap();
+        let preconditioners: [&dyn Preconditioner; 4] = [&Identity, &jacobi, &ilu, &mg];
+        for &method in [Krylov::Cg, Krylov::BiCgStab, Krylov::Gmres].iter() {
+            let mut iterations = Vec::new();
+            for m in preconditioners.iter() {
+                let mut x = vec![0.0; a.nrows()];
+                let c = solve(method, &a, &b, &mut x, *m, &params);
+                assert!(c.converged, "{:?} {:?}", method, c);
+                assert_eq!(c.history.len(), c.iterations + 1);
+                assert!(true_residual(&a, &b, &x) <= 1.01e-8 * norm(&b), "{:?} {:?}", method, c);
+                iterations.push(c.iterations);
+            }
+            // AMG beats ILU(0) beats Jacobi
+            assert!(iterations[3] < iterations[2] && iterations[2] < iterations[1], "{:?} {:?}", method, iterations);
+            assert!(iterations[3] <= 10, "{:?} {:?}", method, iterations);
+        }
+
+        // CG residuals of the AMG preconditioned problem decrease, limits are kept
+        let mut x = vec![0.0; a.nrows()];
+        let c = solve(Krylov::Cg, &a, &b, &mut x, &mg, &params);
+        assert!(c.history.windows(2).all(|w| w[1] < w[0]), "{:?}", c.history);
+        let short = KrylovParams { max_iterations: 3, ..KrylovParams::default() };
+        for &method in [Krylov::Cg, Krylov::BiCgStab, Krylov::Gmres].iter() {
+            let mut x = vec![0.0; a.nrows()];
+            let c = solve(method, &a, &b, &mut x, &Identity, &short);
+            assert!(!c.converged && c.iterations == 3, "{:?} {:?}", method, c);
+            // the initial guess is already the solution
+            let c = solve(method, &a, &vec![0.0; a.nrows()], &mut vec![0.0; a.nrows()], &Identity, &params);
+            assert!(c.converged && c.iterations == 0);
+        }
+    }
+
+    #[test]
+    fn check_nonsymmetric() {
+        let a = convection_diffusion_2d(32, 32, 2.0);
+        let b = rhs(a.nrows());
+        let mg = {
+            let hierarchy = Hierarchy::new(a.clone(), &AmgParams::default()).unwrap();
+            let params = SolveParams { smoother: Smoother::GaussSeidel, ..SolveParams::default() };
+            Multigrid::new(hierarchy, params).unwrap()
+        };
+        let ilu = Ilu0::new(&a).unwrap();
+        for &method in [Krylov::BiCgStab, Krylov::Gmres].iter() {
+            for m in [&ilu as &dyn Preconditioner, &mg] {
+                let mut x = vec![0.0; a.nrows()];
+                let c = solve(method, &a, &b, &mut x, m, &KrylovParams::default());
+                assert!(c.converged, "{:?} {:?}", method, c);
+                assert!(true_residual(&a, &b, &x) <= 1.01e-8 * norm(&b));
+            }
+        }
+
+        // GMRES: without restarts it needs the fewest iterations, the minimized residual
+        // never grows
+        let mut counts = Vec::new();
+        for &restart in [5, 10, 100].iter() {
+            let params = KrylovParams { restart, ..KrylovParams::default() };
+            let mut x = vec![0.0; a.nrows()];
+            let c = solve(Krylov::Gmres, &a, &b, &mut x, &Identity, &params);
+            assert!(c.converged, "{} {:?}", restart, c);
+            assert!(c.history.windows(2).all(|w| w[1] <= w[0] * (1.0 + 1e-10)));
+            counts.push(c.iterations);
+        }
+        assert!(counts[2] <= counts[0] && counts[2] <= counts[1], "{:?}", counts);
+        assert_eq!("bicgstab".parse::<Krylov>(), Ok(Krylov::BiCgStab));
+    }
+}
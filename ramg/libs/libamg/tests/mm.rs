@@ -1,23 +1,123 @@
 #[cfg(test)]
 mod tests {
-    use std::env;
+    use libamg::io::mm::{create_complex_csr, create_csr};
     use libamg::io::MatrixMarketReader;
+    use nalgebra::DMatrix;
+    use num_complex::Complex64;
     use std::path::Path;
 
+    // the matrices of the fixtures, real and imaginary parts; the complex fixtures of
+    // the general, symmetric and skew-symmetric matrices add the imaginary part
+    const GENERAL: ([[f64; 4]; 3], [[f64; 4]; 3]) = (
+        [[1., 0., 2., 0.], [0., 3., 0., 4.], [5., 0., 6., -7.]],
+        [[1., 0., -1., 0.], [0., 2., 0., 0.], [0., 0., 0., 1.]],
+    );
+    const SYMMETRIC: ([[f64; 3]; 3], [[f64; 3]; 3]) = (
+        [[1., 2., 0.], [2., 3., 4.], [0., 4., 5.]],
+        [[1., 0., 0.], [0., 0., -2.], [0., -2., 0.]],
+    );
+    const SKEW_SYMMETRIC: ([[f64; 3]; 3], [[f64; 3]; 3]) = (
+        [[0., -2., -1.], [2., 0., -4.], [1., 4., 0.]],
+        [[0., -1., 0.], [1., 0., 0.], [0., 0., 0.]],
+    );
+    const HERMITIAN: ([[f64; 3]; 3], [[f64; 3]; 3]) = (
+        [[1., 2., 0.], [2., 3., 0.], [0., 0., 5.]],
+        [[0., -1., 0.], [1., 0., -4.], [0., 4., 0.]],
+    );
+
+    fn dense<const N: usize>(re: &[[f64; N]], im: &[[f64; N]], field: &str) -> DMatrix<Complex64> {
+        DMatrix::from_fn(re.len(), N, |i, j| match field {
+            "complex" => Complex64::new(re[i][j], im[i][j]),
+            "pattern" => Complex64::new(if re[i][j] != 0.0 { 1.0 } else { 0.0 }, 0.0),
+            _ => Complex64::new(re[i][j], 0.0),
+        })
+    }
+
+    fn expected(field: &str, symmetry: &str) -> DMatrix<Complex64> {
+        match symmetry {
+            "general" => dense(&GENERAL.0, &GENERAL.1, field),
+            "symmetric" => dense(&SYMMETRIC.0, &SYMMETRIC.1, field),
+            "skew-symmetric" => dense(&SKEW_SYMMETRIC.0, &SKEW_SYMMETRIC.1, field),
+            _ => dense(&HERMITIAN.0, &HERMITIAN.1, field),
+        }
+    }
+
+    fn read(name: &str) -> Result<MatrixMarketReader, String> {
+        MatrixMarketReader::new(Path::new(".").join("data").join(name).to_str().unwrap())
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
 
+    #[test]
+    fn check_fixtures() {
+        let mut count = 0;
+        for format in ["coordinate", "array"] {
+            for field in ["real", "integer", "complex", "pattern"] {
+                for symmetry in ["general", "symmetric", "skew-symmetric", "hermitian"] {
+                    let supported = (field != "pattern" || (format == "coordinate" && symmetry != "skew-symmetric"))
+                        && (symmetry != "hermitian" || field == "complex");
+                    if !supported {
+                        continue;
+                    }
+                    let name = format!("mm/{}_{}_{}.mtx", format, field, symmetry);
+                    let mm = read(&name).unwrap();
+                    let a = DMatrix::from(&create_complex_csr(&mm));
+                    assert_eq!(a, expected(field, symmetry), "{}", name);
+                    assert_eq!(mm.is_complex(), field == "complex");
+                    if field != "complex" {
+                        let a = DMatrix::from(&create_csr(&mm));
+                        assert_eq!(a.map(|x| Complex64::new(x, 0.0)), expected(field, symmetry), "{}", name);
+                    }
+                    // entries are mirrored, the diagonal is not
+                    if format == "coordinate" {
+                        let nnz = expected(field, symmetry).iter().filter(|&&x| x != Complex64::new(0.0, 0.0)).count();
+                        assert_eq!(mm.nnz(), nnz, "{}", name);
+                    }
+                    count += 1;
+                }
+            }
+        }
+        assert_eq!(count, 22);
+    }
+
+    #[test]
+    fn check_errors() {
+        let parse = |s: &str| s.parse::<MatrixMarketReader>().err().unwrap_or_default();
+        assert!(parse("%%MatrixMarket matrix array pattern general\n1 1\n").contains("pattern"));
+        assert!(parse("%%MatrixMarket matrix coordinate real hermitian\n1 1 0\n").contains("hermitian"));
+        assert!(parse("%%MatrixMarket matrix coordinate pattern skew-symmetric\n1 1 0\n").contains("skew"));
+        assert!(parse("%%MatrixMarket matrix coordinate real symmetric\n2 3 0\n").contains("2 x 3"));
+        assert!(parse("%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1.0\n").contains("missing"));
+        assert!(parse("%%MatrixMarket matrix coordinate real general\n2 2 1\n1 1 1.0\n2 2 1.0\n").contains("more"));
+        assert!(parse("%%MatrixMarket matrix coordinate real general\n2 2 1\n3 1 1.0\n").contains("out of"));
+        assert!(parse("%%MatrixMarket matrix coordinate real general\n2 2 1\n1 1 x\n").contains("`x`"));
+        assert!(parse("%%MatrixMarket matrix coordinate real skew-symmetric\n2 2 1\n1 1 1.0\n").contains("diagonal"));
+        assert!(parse("%%MatrixMarket matrix array real general\n2 2\n1\n2\n3\n").contains("missing"));
+        assert!(parse("%MatrixMarket matrix array real general\n").contains("banner"));
+        assert!(parse("").contains("empty"));
+        assert!(read("no such file.mtx").is_err());
+
+        // the banner is case insensitive, `double` is `real`
+        let mm: MatrixMarketReader = "%%MatrixMarket MATRIX Array Double General\n1 2\n1.5\n-2\n".parse().unwrap();
+        assert_eq!(DMatrix::from(&create_csr(&mm)), DMatrix::from_row_slice(1, 2, &[1.5, -2.0]));
+    }
+
     #[test]
     fn check_mm_reader() {
-        let cur_path = env::current_dir().unwrap();
-        println!("current path : {}", cur_path.display());
-        let matrices_path = Path::new(".").join("data");
-        let matrix_path = matrices_path.join("iDA_SIPG_2_test_problem_2_regular_1.m.mtx");
-
-        let mm = MatrixMarketReader::new(matrix_path.to_str().unwrap());
-    
-	assert_eq!(1, 2);
+        let mm = read("iDA_SIPG_2_test_problem_2_regular_1.m.mtx").unwrap();
+        assert_eq!((mm.nrows(), mm.ncols(), mm.nnz()), (5916, 5916, 135548));
+        let a = create_csr(&mm);
+        assert_eq!(a.nnz(), 135548);
+        assert!((a.get_entry(0, 0).unwrap().into_value() - 1.0).abs() < 1e-15);
+        assert_eq!(a.get_entry(1, 0).unwrap().into_value(), -6.938893903907228e-17);
+
+        // a complex symmetric matrix: 4089 stored entries, 841 of them diagonal
+        let mm = read("young1c.mtx").unwrap();
+        assert_eq!(mm.nnz(), 2 * 4089 - 841);
+        let a = create_complex_csr(&mm);
+        assert_eq!(a.transpose(), a);
     }
 }
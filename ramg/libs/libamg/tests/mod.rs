@@ -1 +1,3 @@
 mod mm;
+mod amg;
+mod krylov;
@@ -1,46 +1,264 @@
-#[macro_use]
 extern crate clap;
 
 extern crate libamg;
 extern crate nalgebra as na;
 
+use clap::{value_parser, Arg, Command};
+use libamg::amg::{AmgParams, Coarsening, Cycle, Hierarchy, Interpolation, Method, Multigrid, Smoother, SolveParams};
 use libamg::io::MatrixMarketReader;
+use libamg::krylov::{Identity, Ilu0, Jacobi, Krylov, KrylovParams, Preconditioner};
+use std::process::exit;
 use std::time::{Duration, Instant};
 use na::io::cs_matrix_from_matrix_market;
 
+// the real matrix of a MatrixMarket file or the error
+fn read_matrix(name: &str) -> MatrixMarketReader {
+    match MatrixMarketReader::new(name) {
+        Ok(mm) if mm.is_complex() => {
+            eprintln!("{}: complex matrices are not supported", name);
+            exit(1);
+        }
+        Ok(mm) => mm,
+        Err(e) => {
+            eprintln!("{}: {}", name, e);
+            exit(1);
+        }
+    }
+}
+
+fn multigrid(hierarchy: Hierarchy, params: SolveParams) -> Multigrid {
+    match Multigrid::new(hierarchy, params) {
+        Ok(multigrid) => multigrid,
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1);
+        }
+    }
+}
+
+// the residual norms, the initial one and one per cycle or iteration, with their reduction factors
+fn print_history(step: &str, history: &[f64]) {
+    println!("{:>9} {:>12} {:>8}", step, "residual", "factor");
+    for (k, r) in history.iter().enumerate() {
+        if k == 0 {
+            println!("{:>9} {:>12.4e}", k, r);
+        } else {
+            println!("{:>9} {:>12.4e} {:>8.3}", k, r, r / history[k - 1]);
+        }
+    }
+    let steps = history.len() - 1;
+    println!(
+        "{} {}s, average reduction factor {:.3}",
+        steps,
+        step,
+        (history[steps] / history[0]).powf(1.0 / steps.max(1) as f64)
+    );
+}
+
 fn main()
 {
-    let matches = clap_app!(ramg =>
-            (version: "0.0.1")
-            (author: "Alexander Samoilov <alexander.samoilov@gmail.com>")
-            (@arg SET_MATRIX: -A --matrix +takes_value "System matrix in the MatrixMarket format.")
-            (@arg SET_BLOCKSIZE: -b --block-size +takes_value "The block size of the system matrix.")
-        ).get_matches();
-
-    let block_size = if let Ok(block_size) = value_t!(matches, "SET_BLOCKSIZE", usize) {
+    let matches = Command::new("ramg")
+        .version("0.0.1")
+        .author("Alexander Samoilov <alexander.samoilov@gmail.com>")
+        .arg(Arg::new("SET_MATRIX").short('A').long("matrix")
+             .help("System matrix in the MatrixMarket format."))
+        .arg(Arg::new("SET_RHS").short('b').long("rhs")
+             .help("Right hand side in the MatrixMarket format, an n x 1 coordinate or array matrix; all ones by default."))
+        .arg(Arg::new("SET_BLOCKSIZE").long("block-size")
+             .value_parser(value_parser!(usize))
+             .help("The block size of the system matrix."))
+        .arg(Arg::new("SET_METHOD").long("method")
+             .value_parser(|s: &str| s.parse::<Method>())
+             .help("AMG method: classical|sa (smoothed aggregation)."))
+        .arg(Arg::new("SET_THETA").long("theta")
+             .value_parser(value_parser!(f64))
+             .help("Strength of connection threshold, 0.25 (classical) or 0.08 (sa) by default."))
+        .arg(Arg::new("SET_COARSENING").long("coarsening")
+             .value_parser(|s: &str| s.parse::<Coarsening>())
+             .help("C/F splitting: rs|pmis."))
+        .arg(Arg::new("SET_INTERPOLATION").long("interpolation")
+             .value_parser(|s: &str| s.parse::<Interpolation>())
+             .help("Interpolation: classical|direct."))
+        .arg(Arg::new("SET_MAX_LEVELS").long("max-levels")
+             .value_parser(value_parser!(usize))
+             .help("The maximal number of levels of the hierarchy."))
+        .arg(Arg::new("SET_MAX_COARSE").long("max-coarse")
+             .value_parser(value_parser!(usize))
+             .help("Stop coarsening at levels with at most this many rows."))
+        .arg(Arg::new("SET_NULLSPACE").long("nullspace")
+             .help("Near-nullspace vectors (columns) in the MatrixMarket format, sa only; constants by default."))
+        .arg(Arg::new("SET_OMEGA").long("omega")
+             .value_parser(value_parser!(f64))
+             .help("Prolongator smoothing weight relative to the spectral radius of D^-1 A, 4/3 by default."))
+        .arg(Arg::new("SET_CYCLE").long("cycle")
+             .value_parser(|s: &str| s.parse::<Cycle>())
+             .help("Multigrid cycle: v|w|f."))
+        .arg(Arg::new("SET_SMOOTHER").long("smoother")
+             .value_parser(|s: &str| s.parse::<Smoother>())
+             .help("Smoother: jacobi|gs|backward-gs|symmetric-gs|l1-jacobi|chebyshev."))
+        .arg(Arg::new("SET_PRE_SWEEPS").long("pre-sweeps")
+             .value_parser(value_parser!(usize))
+             .help("Smoothing sweeps before the coarse grid correction, 1 by default."))
+        .arg(Arg::new("SET_POST_SWEEPS").long("post-sweeps")
+             .value_parser(value_parser!(usize))
+             .help("Smoothing sweeps after the coarse grid correction, 1 by default."))
+        .arg(Arg::new("SET_JACOBI_WEIGHT").long("jacobi-weight")
+             .value_parser(value_parser!(f64))
+             .help("Damped Jacobi weight, 2/3 by default."))
+        .arg(Arg::new("SET_CHEBYSHEV_DEGREE").long("chebyshev-degree")
+             .value_parser(value_parser!(usize))
+             .help("Chebyshev polynomial degree, 3 by default."))
+        .arg(Arg::new("SET_MAX_CYCLES").long("max-cycles")
+             .value_parser(value_parser!(usize))
+             .help("The maximal number of cycles, 100 by default."))
+        .arg(Arg::new("SET_MAX_DIRECT").long("max-direct")
+             .value_parser(value_parser!(usize))
+             .help("The most rows of the coarsest level, solved by dense LU, 4000 by default."))
+        .arg(Arg::new("SET_TOLERANCE").long("tolerance")
+             .value_parser(value_parser!(f64))
+             .help("Stop at this residual relative to the right hand side, 1e-8 by default."))
+        .arg(Arg::new("SET_KRYLOV").long("krylov")
+             .value_parser(|s: &str| s.parse::<Krylov>())
+             .help("Krylov method: cg|bicgstab|gmres; stand-alone AMG cycles by default."))
+        .arg(Arg::new("SET_PRECONDITIONER").long("preconditioner")
+             .value_parser(["amg", "jacobi", "ilu0", "none"])
+             .help("Preconditioner of the Krylov method, amg by default."))
+        .arg(Arg::new("SET_MAX_ITERATIONS").long("max-iterations")
+             .value_parser(value_parser!(usize))
+             .help("The maximal number of Krylov iterations, 500 by default."))
+        .arg(Arg::new("SET_RESTART").long("restart")
+             .value_parser(value_parser!(usize))
+             .help("GMRES restart length, 30 by default."))
+        .get_matches();
+
+    let block_size = if let Some(&block_size) = matches.get_one::<usize>("SET_BLOCKSIZE") {
             block_size
         } else {
             1 // default value
         };
     println!("block size: {}", block_size);
 
-    if let Ok(matrix_name) = value_t!(matches, "SET_MATRIX", String) {
+    let defaults = match matches.get_one::<Method>("SET_METHOD") {
+        Some(Method::SmoothedAggregation) => AmgParams::smoothed_aggregation(),
+        _ => AmgParams::default(),
+    };
+    let params = AmgParams {
+        theta: *matches.get_one::<f64>("SET_THETA").unwrap_or(&defaults.theta),
+        coarsening: *matches.get_one::<Coarsening>("SET_COARSENING").unwrap_or(&defaults.coarsening),
+        interpolation: *matches.get_one::<Interpolation>("SET_INTERPOLATION").unwrap_or(&defaults.interpolation),
+        max_levels: *matches.get_one::<usize>("SET_MAX_LEVELS").unwrap_or(&defaults.max_levels),
+        max_coarse: *matches.get_one::<usize>("SET_MAX_COARSE").unwrap_or(&defaults.max_coarse),
+        block_size,
+        omega: *matches.get_one::<f64>("SET_OMEGA").unwrap_or(&defaults.omega),
+        ..defaults
+    };
+
+    let defaults = SolveParams::default();
+    let solve_params = SolveParams {
+        cycle: *matches.get_one::<Cycle>("SET_CYCLE").unwrap_or(&defaults.cycle),
+        smoother: *matches.get_one::<Smoother>("SET_SMOOTHER").unwrap_or(&defaults.smoother),
+        pre_sweeps: *matches.get_one::<usize>("SET_PRE_SWEEPS").unwrap_or(&defaults.pre_sweeps),
+        post_sweeps: *matches.get_one::<usize>("SET_POST_SWEEPS").unwrap_or(&defaults.post_sweeps),
+        jacobi_weight: *matches.get_one::<f64>("SET_JACOBI_WEIGHT").unwrap_or(&defaults.jacobi_weight),
+        chebyshev_degree: *matches.get_one::<usize>("SET_CHEBYSHEV_DEGREE").unwrap_or(&defaults.chebyshev_degree),
+        max_cycles: *matches.get_one::<usize>("SET_MAX_CYCLES").unwrap_or(&defaults.max_cycles),
+        tolerance: *matches.get_one::<f64>("SET_TOLERANCE").unwrap_or(&defaults.tolerance),
+        max_direct: *matches.get_one::<usize>("SET_MAX_DIRECT").unwrap_or(&defaults.max_direct),
+    };
+
+    let defaults = KrylovParams::default();
+    let krylov_params = KrylovParams {
+        max_iterations: *matches.get_one::<usize>("SET_MAX_ITERATIONS").unwrap_or(&defaults.max_iterations),
+        tolerance: solve_params.tolerance,
+        restart: *matches.get_one::<usize>("SET_RESTART").unwrap_or(&defaults.restart),
+    };
+
+    if let Some(matrix_name) = matches.get_one::<String>("SET_MATRIX") {
         println!("the matrix: {}", matrix_name);
         let mut start = Instant::now();
-        let mmr = MatrixMarketReader::new(&matrix_name);
+        let mmr = read_matrix(matrix_name);
         let mut duration = start.elapsed();
         println!("Time elapsed in `MatrixMarketReader::new()` is: {:?}", duration);
         start = Instant::now();
-        let csr = libamg::io::mm::create_csr(&mmr.unwrap());
+        let csr = libamg::io::mm::create_csr(&mmr);
         duration = start.elapsed();
         println!("Time elapsed in `create_csr()` is: {:?}", duration);
         //println!("csr: {:?}", &csr);
 
         start = Instant::now();
-        let cs = cs_matrix_from_matrix_market::<f64, &str>(&matrix_name);
+        let cs = cs_matrix_from_matrix_market::<f64, &str>(matrix_name);
         duration = start.elapsed();
         println!("Time elapsed in `cs_matrix_from_matrix_market()` is: {:?}", duration);
         //println!("cs: {:?}", &cs);
 
+        let nullspace = matches.get_one::<String>("SET_NULLSPACE").map(|name| {
+            println!("the near-nullspace: {}", name);
+            let b = libamg::io::mm::create_csr(&read_matrix(name));
+            na::DMatrix::from(&b)
+        });
+
+        println!("{:?}", params);
+        start = Instant::now();
+        let hierarchy = match Hierarchy::with_nullspace(csr, &params, nullspace) {
+            Ok(hierarchy) => hierarchy,
+            Err(e) => {
+                eprintln!("{}", e);
+                exit(1);
+            }
+        };
+        duration = start.elapsed();
+        println!("Time elapsed in `Hierarchy::new()` is: {:?}", duration);
+        println!("{}", hierarchy);
+
+        let n = hierarchy.levels[0].a.nrows();
+        let b = match matches.get_one::<String>("SET_RHS") {
+            Some(rhs_name) => {
+                println!("the right hand side: {}", rhs_name);
+                let rhs = libamg::io::mm::create_csr(&read_matrix(rhs_name));
+                if rhs.nrows() != n || rhs.ncols() != 1 {
+                    eprintln!("the right hand side is {} x {}, expected {} x 1", rhs.nrows(), rhs.ncols(), n);
+                    exit(1);
+                }
+                na::DMatrix::from(&rhs).column(0).iter().cloned().collect()
+            }
+            None => vec![1.0; n],
+        };
+
+        println!("{:?}", solve_params);
+        let mut x = vec![0.0; n];
+        match matches.get_one::<Krylov>("SET_KRYLOV") {
+            None => {
+                start = Instant::now();
+                let history = multigrid(hierarchy, solve_params).solve(&b, &mut x);
+                duration = start.elapsed();
+                println!("Time elapsed in `Multigrid::solve()` is: {:?}", duration);
+                print_history("cycle", &history);
+            }
+            Some(&method) => {
+                let name = matches.get_one::<String>("SET_PRECONDITIONER").map_or("amg", |s| s.as_str());
+                println!("{:?} preconditioned by {}, {:?}", method, name, krylov_params);
+                start = Instant::now();
+                let a = hierarchy.levels[0].a.clone();
+                let preconditioner: Box<dyn Preconditioner> = match name {
+                    "amg" => Box::new(multigrid(hierarchy, solve_params)),
+                    "jacobi" => Box::new(Jacobi::new(&a)),
+                    "ilu0" => match Ilu0::new(&a) {
+                        Ok(ilu) => Box::new(ilu),
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            exit(1);
+                        }
+                    },
+                    _ => Box::new(Identity),
+                };
+                let convergence = libamg::krylov::solve(method, &a, &b, &mut x, preconditioner.as_ref(), &krylov_params);
+                duration = start.elapsed();
+                println!("Time elapsed in `krylov::solve()` is: {:?}", duration);
+                print_history("iteration", &convergence.history);
+                if !convergence.converged {
+                    println!("not converged in {} iterations", convergence.iterations);
+                }
+            }
+        }
     }
 }
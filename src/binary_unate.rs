@@ -20,6 +20,12 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+#![cfg_attr(feature = "use-nightly", feature(test))]
+
+#[cfg(feature = "use-nightly")]
+extern crate test;
+
+extern crate rayon;
 
 // program args
 use std::env;
@@ -33,35 +39,380 @@ use std::path::Path;
 
 // a double linked list
 use std::collections::LinkedList;
+use std::collections::HashMap;
 use std::iter::FromIterator;
 
+// outputs complemented in parallel
+use rayon::prelude::*;
+
 // for Ord
 use std::cmp::Ordering;
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+// for the packed cube
+use std::fmt;
+use std::ops::Index;
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 enum TriLogic {
     True = 0b01,
     False = 0b10,
     DontCare = 0b11,
 }
 
-type CubeList = LinkedList<Vec<TriLogic>>;
+// literals are packed by 2 bits in `TriLogic` encoding, 32 literals per word
+#[cfg(not(feature = "unpacked-cubes"))]
+const LITERALS_PER_WORD: usize = 32;
+#[cfg(not(feature = "unpacked-cubes"))]
+const LOW_BITS: u64 = 0x5555_5555_5555_5555;
+
+// lookup table to hand out `&TriLogic` for a packed literal, 0b00 is never stored
+static LITERALS: [TriLogic; 4] = [
+    TriLogic::DontCare,
+    TriLogic::True,
+    TriLogic::False,
+    TriLogic::DontCare,
+];
+
+// a product term with literals packed into u64 words so that
+// intersection, containment and cofactoring are word-wise AND/OR,
+// unused bits of the last word are kept as dont cares
+#[cfg(not(feature = "unpacked-cubes"))]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Cube {
+    num_vars: usize,
+    words: Vec<u64>,
+}
+
+#[cfg(not(feature = "unpacked-cubes"))]
+impl Cube {
+    fn dont_cares(num_vars: usize) -> Cube {
+        let num_words = num_vars.div_ceil(LITERALS_PER_WORD);
+        Cube {
+            num_vars,
+            words: vec![!0u64; num_words],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.num_vars
+    }
+
+    #[inline]
+    fn code(&self, i: usize) -> u64 {
+        (self.words[i / LITERALS_PER_WORD] >> (2 * (i % LITERALS_PER_WORD))) & 0b11
+    }
+
+    #[inline]
+    fn set(&mut self, i: usize, term: TriLogic) {
+        let shift = 2 * (i % LITERALS_PER_WORD);
+        let w = &mut self.words[i / LITERALS_PER_WORD];
+        *w = (*w & !(0b11 << shift)) | ((term as u64) << shift);
+    }
+
+    fn is_all_dont_cares(&self) -> bool {
+        self.words.iter().all(|&w| w == !0u64)
+    }
+
+    fn count_dont_cares(&self) -> usize {
+        let padding = self.words.len() * LITERALS_PER_WORD - self.num_vars;
+        let dont_cares: u32 = self.words
+            .iter()
+            .map(|&w| (w & (w >> 1) & LOW_BITS).count_ones())
+            .sum();
+        dont_cares as usize - padding
+    }
+
+    // cubes are disjoint iff there is a variable with opposite literals,
+    // i.e. their AND has an empty 0b00 field
+    fn intersects(&self, other: &Cube) -> bool {
+        self.words.iter().zip(other.words.iter()).all(|(&a, &b)| {
+            let w = a & b;
+            (w | (w >> 1)) & LOW_BITS == LOW_BITS
+        })
+    }
+
+    fn intersection(&self, other: &Cube) -> Option<Cube> {
+        if self.intersects(other) {
+            Some(Cube {
+                num_vars: self.num_vars,
+                words: self.words.iter().zip(other.words.iter()).map(|(&a, &b)| a & b).collect(),
+            })
+        } else {
+            None
+        }
+    }
+
+    // `self` contains `other` iff every literal of `self` is also a literal of `other`
+    fn contains(&self, other: &Cube) -> bool {
+        self.words.iter().zip(other.words.iter()).all(|(&a, &b)| a & b == b)
+    }
+
+    // both bits set at the positions of literals, zero at dont cares
+    fn literal_mask(&self) -> Vec<u64> {
+        self.words
+            .iter()
+            .map(|&w| {
+                let literals = !(w & (w >> 1)) & LOW_BITS;
+                literals | (literals << 1)
+            })
+            .collect()
+    }
+
+    // the number of variables with opposite literals
+    fn distance(&self, other: &Cube) -> usize {
+        let opposite: u32 = self.words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(&a, &b)| {
+                let w = a & b;
+                (!(w | (w >> 1)) & LOW_BITS).count_ones()
+            })
+            .sum();
+        opposite as usize
+    }
+
+    // the consensus of cubes at distance 1: their intersection with
+    // the opposite variable raised to dont care
+    fn consensus(&self, other: &Cube) -> Option<Cube> {
+        if self.distance(other) != 1 {
+            return None;
+        }
+        Some(Cube {
+            num_vars: self.num_vars,
+            words: self.words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(&a, &b)| {
+                    let w = a & b;
+                    let empty = !(w | (w >> 1)) & LOW_BITS;
+                    w | empty | (empty << 1)
+                })
+                .collect(),
+        })
+    }
+
+    // cofactor w.r.t. the cube `c`, `None` if the cubes are disjoint
+    fn cofactor(&self, c: &Cube) -> Option<Cube> {
+        if self.intersects(c) {
+            Some(Cube {
+                num_vars: self.num_vars,
+                words: self.words.iter().zip(c.literal_mask().iter()).map(|(&a, &m)| a | m).collect(),
+            })
+        } else {
+            None
+        }
+    }
+
+    // the smallest cube containing both
+    fn supercube_with(&mut self, other: &Cube) {
+        for (s, w) in self.words.iter_mut().zip(other.words.iter()) {
+            *s |= *w;
+        }
+    }
+}
+
+// the former representation, a `TriLogic` per variable, kept as the baseline
+// of the packed cubes for `make binary_unate_bench_unpacked`
+#[cfg(feature = "unpacked-cubes")]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Cube {
+    terms: Vec<TriLogic>,
+}
+
+#[cfg(feature = "unpacked-cubes")]
+impl Cube {
+    fn dont_cares(num_vars: usize) -> Cube {
+        Cube {
+            terms: vec![TriLogic::DontCare; num_vars],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    #[inline]
+    fn code(&self, i: usize) -> u64 {
+        self.terms[i].clone() as u64
+    }
+
+    #[inline]
+    fn set(&mut self, i: usize, term: TriLogic) {
+        self.terms[i] = term;
+    }
+
+    // the cube of `op` applied to the codes of every variable, 0b00 reads as a dont care
+    fn combine<F: Fn(u64, u64) -> u64>(&self, other: &Cube, op: F) -> Cube {
+        Cube {
+            terms: (0..self.len())
+                .map(|i| LITERALS[op(self.code(i), other.code(i)) as usize].clone())
+                .collect(),
+        }
+    }
+
+    fn is_all_dont_cares(&self) -> bool {
+        self.terms.iter().all(|t| *t == TriLogic::DontCare)
+    }
+
+    fn count_dont_cares(&self) -> usize {
+        self.terms.iter().filter(|t| **t == TriLogic::DontCare).count()
+    }
+
+    fn intersects(&self, other: &Cube) -> bool {
+        (0..self.len()).all(|i| self.code(i) & other.code(i) != 0)
+    }
+
+    fn intersection(&self, other: &Cube) -> Option<Cube> {
+        if self.intersects(other) {
+            Some(self.combine(other, |a, b| a & b))
+        } else {
+            None
+        }
+    }
+
+    fn contains(&self, other: &Cube) -> bool {
+        (0..self.len()).all(|i| self.code(i) & other.code(i) == other.code(i))
+    }
+
+    fn distance(&self, other: &Cube) -> usize {
+        (0..self.len()).filter(|&i| self.code(i) & other.code(i) == 0).count()
+    }
+
+    fn consensus(&self, other: &Cube) -> Option<Cube> {
+        if self.distance(other) != 1 {
+            return None;
+        }
+        Some(self.combine(other, |a, b| a & b))
+    }
+
+    fn cofactor(&self, c: &Cube) -> Option<Cube> {
+        if self.intersects(c) {
+            Some(self.combine(c, |a, m| if m == 0b11 { a } else { 0b11 }))
+        } else {
+            None
+        }
+    }
+
+    fn supercube_with(&mut self, other: &Cube) {
+        *self = self.combine(other, |a, b| a | b);
+    }
+}
+
+// the operations on the literal codes, the same for both representations
+impl Cube {
+    fn iter<'a>(&'a self) -> impl Iterator<Item = TriLogic> + 'a {
+        (0..self.len()).map(move |i| self[i].clone())
+    }
+
+    // a # b: the cubes of `self` with a literal of `other` complemented,
+    // the result covers exactly the minterms of `self` not in `other`
+    fn sharp(&self, other: &Cube) -> Vec<Cube> {
+        if !self.intersects(other) {
+            return vec![self.clone()];
+        }
+        (0..self.len())
+            .filter_map(|i| {
+                let rest = self.code(i) & (other.code(i) ^ 0b11);
+                if rest == 0 {
+                    None
+                } else {
+                    let mut cube = self.clone();
+                    cube.set(i, LITERALS[rest as usize].clone());
+                    Some(cube)
+                }
+            })
+            .collect()
+    }
+
+    // a #d b: same minterms as the sharp product but with pairwise disjoint cubes,
+    // the literals before the complemented one are intersected with `other`
+    fn disjoint_sharp(&self, other: &Cube) -> Vec<Cube> {
+        if !self.intersects(other) {
+            return vec![self.clone()];
+        }
+        let mut prefix = self.clone();
+        let mut result = Vec::new();
+        for i in 0..self.len() {
+            let rest = self.code(i) & (other.code(i) ^ 0b11);
+            if rest != 0 {
+                let mut cube = prefix.clone();
+                cube.set(i, LITERALS[rest as usize].clone());
+                result.push(cube);
+            }
+            let common = self.code(i) & other.code(i);
+            prefix.set(i, LITERALS[common as usize].clone());
+        }
+        result
+    }
+}
+
+impl Index<usize> for Cube {
+    type Output = TriLogic;
+
+    fn index(&self, i: usize) -> &TriLogic {
+        &LITERALS[self.code(i) as usize]
+    }
+}
+
+impl From<Vec<TriLogic>> for Cube {
+    fn from(terms: Vec<TriLogic>) -> Cube {
+        let mut cube = Cube::dont_cares(terms.len());
+        for (i, term) in terms.into_iter().enumerate() {
+            cube.set(i, term);
+        }
+        cube
+    }
+}
+
+impl fmt::Debug for Cube {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[test]
+fn packed_cube_test() {
+    let mut terms = vec![TriLogic::DontCare; 70];
+    terms[0] = TriLogic::True;
+    terms[33] = TriLogic::False;
+    terms[69] = TriLogic::True;
+    let cube = Cube::from(terms.clone());
+    assert!(cube.len() == 70);
+    #[cfg(not(feature = "unpacked-cubes"))]
+    assert!(cube.words.len() == 3, "70 literals in 3 words");
+    assert!(cube.iter().collect::<Vec<_>>() == terms, "round trip of literals");
+    assert!(cube.count_dont_cares() == 67, "padding is not counted");
+    assert!(!cube.is_all_dont_cares() && Cube::dont_cares(70).is_all_dont_cares());
+
+    let mut other = Cube::dont_cares(70);
+    other.set(33, TriLogic::True);
+    assert!(!cube.intersects(&other), "x33' and x33 are disjoint");
+    other.set(33, TriLogic::False);
+    assert!(other.contains(&cube) && !cube.contains(&other), "x33' contains x0 x33' x69");
+    assert!(other.intersection(&cube) == Some(cube.clone()));
+    assert!(cube.cofactor(&other).unwrap()[33] == TriLogic::DontCare, "x33 is cofactored");
+}
+
+type CubeList = LinkedList<Cube>;
+
+#[cfg(test)]
+fn cube_list(cubes: Vec<Vec<TriLogic>>) -> CubeList {
+    cubes.into_iter().map(Cube::from).collect()
+}
 
 fn cubelist_contains_all_dont_cares_cube(F: &CubeList) -> bool {
     // `any` of the cubes contains `all` dont cares
-    F.iter().any(|c: &Vec<TriLogic>| {
-        ((*c).iter().all(|t: &TriLogic| *t == TriLogic::DontCare))
-    })
+    F.iter().any(|c: &Cube| c.is_all_dont_cares())
 }
 
 #[test]
 fn test_contains_all_dont_cares_cube() {
     assert!(
-        !cubelist_contains_all_dont_cares_cube(&LinkedList::from_iter(vec![])),
+        !cubelist_contains_all_dont_cares_cube(&LinkedList::new()),
         "case0: empty cubelist"
     );
     assert!(
-        !cubelist_contains_all_dont_cares_cube(&LinkedList::from_iter(vec![
+        !cubelist_contains_all_dont_cares_cube(&cube_list(vec![
             vec![
                 TriLogic::True,
                 TriLogic::DontCare,
@@ -71,7 +422,7 @@ fn test_contains_all_dont_cares_cube() {
         "case1: [[True, DontCare, DontCare]]"
     );
     assert!(
-        cubelist_contains_all_dont_cares_cube(&LinkedList::from_iter(vec![
+        cubelist_contains_all_dont_cares_cube(&cube_list(vec![
             vec![
                 TriLogic::DontCare,
                 TriLogic::DontCare,
@@ -87,23 +438,18 @@ fn test_contains_all_dont_cares_cube() {
     );
 }
 
-fn count_all_dont_cares(cube: &[TriLogic]) -> usize {
-    (*cube)
-        .iter()
-        .filter(|term| **term == TriLogic::DontCare)
-        .count()
+#[cfg(test)]
+fn count_all_dont_cares(cube: &Cube) -> usize {
+    cube.count_dont_cares()
 }
 
-fn count_all_not_dont_cares(cube: &[TriLogic]) -> usize {
-    (*cube)
-        .iter()
-        .filter(|term| **term != TriLogic::DontCare)
-        .count()
+fn count_all_not_dont_cares(cube: &Cube) -> usize {
+    cube.len() - cube.count_dont_cares()
 }
 
 #[test]
 fn test_count_don_cares() {
-    let cube = [
+    let cube = Cube::from(vec![
         TriLogic::True,
         TriLogic::False,
         TriLogic::DontCare,
@@ -111,7 +457,7 @@ fn test_count_don_cares() {
         TriLogic::False,
         TriLogic::DontCare,
         TriLogic::True,
-    ];
+    ]);
     assert!(count_all_dont_cares(&cube) == 3, "count all dont_cares");
     assert!(
         count_all_not_dont_cares(&cube) == 4,
@@ -177,7 +523,7 @@ fn most_binate_variable(F: &CubeList) -> usize {
     let mut tie = vec![BinateVarAttrs(0, 0, 0); num_vars];
     for c in F.iter() {
         for i in 0..(*c).len() {
-            let term = (*c)[i].clone();
+            let term = c[i].clone();
             let j = i as isize;
             if term == TriLogic::True {
                 tie[i] = match tie[i] {
@@ -227,15 +573,14 @@ fn most_binate_variable(F: &CubeList) -> usize {
 fn positiveCofactor(F: &CubeList, x: usize) -> CubeList {
     let mut cofactor_list: CubeList = LinkedList::new();
     for c in F.iter() {
-        let mut cube = c.clone();
-        let term_val: TriLogic = cube[x].clone();
-        match term_val {
+        match c[x] {
             TriLogic::True => {
-                cube[x] = TriLogic::DontCare;
+                let mut cube = c.clone();
+                cube.set(x, TriLogic::DontCare);
                 cofactor_list.push_back(cube)
             }
             TriLogic::False => {} // => remove this cube
-            TriLogic::DontCare => cofactor_list.push_back(cube), // leave it as is
+            TriLogic::DontCare => cofactor_list.push_back(c.clone()), // leave it as is
         }
     }
     cofactor_list
@@ -244,15 +589,14 @@ fn positiveCofactor(F: &CubeList, x: usize) -> CubeList {
 fn negativeCofactor(F: &CubeList, x: usize) -> CubeList {
     let mut cofactor_list: CubeList = LinkedList::new();
     for c in F.iter() {
-        let mut cube = c.clone();
-        let term_val: TriLogic = cube[x].clone();
-        match term_val {
+        match c[x] {
             TriLogic::True => {} // remove this cube
             TriLogic::False => {
-                cube[x] = TriLogic::DontCare;
+                let mut cube = c.clone();
+                cube.set(x, TriLogic::DontCare);
                 cofactor_list.push_back(cube)
             }
-            TriLogic::DontCare => cofactor_list.push_back(cube), // leave it as is
+            TriLogic::DontCare => cofactor_list.push_back(c.clone()), // leave it as is
         }
     }
     cofactor_list
@@ -260,7 +604,7 @@ fn negativeCofactor(F: &CubeList, x: usize) -> CubeList {
 
 #[test]
 fn cofactors_test() {
-    let clist: CubeList = LinkedList::from_iter(vec![
+    let clist: CubeList = cube_list(vec![
         vec![
             TriLogic::True,
             TriLogic::True,
@@ -282,57 +626,54 @@ fn cofactors_test() {
     assert!(fc.len() == 1);
     assert!(
         fa.front().unwrap() ==
-            &[
+            &Cube::from(vec![
                 TriLogic::DontCare,
                 TriLogic::True,
                 TriLogic::DontCare,
                 TriLogic::True,
-            ],
+            ]),
         "fa -- 1st cube"
     );
     assert!(
         fa.pop_back() ==
-            Some(vec![
+            Some(Cube::from(vec![
                 TriLogic::DontCare,
                 TriLogic::True,
                 TriLogic::False,
                 TriLogic::DontCare,
-            ]),
+            ])),
         "fa -- 2nd cube"
     );
     assert!(
         fc.front().unwrap() ==
-            &[
+            &Cube::from(vec![
                 TriLogic::True,
                 TriLogic::True,
                 TriLogic::DontCare,
                 TriLogic::True,
-            ],
+            ]),
         "fc -- 1st cube"
     );
 }
 
 // x offset is 1 to distinguish positive x and negative x'
-fn AND(x: isize, F: &CubeList) -> CubeList {
+fn and_in_place(x: isize, F: &mut CubeList) {
     let ind = (x.abs() - 1) as usize;
     // check that cubelist lacks variable x
     assert!(F.iter().all(|c| c[ind] == TriLogic::DontCare));
-    let mut and_list: CubeList = LinkedList::new();
-    for c in F.iter() {
-        let mut cube = c.clone();
-        cube[ind] = if x > 0 {
-            TriLogic::True
-        } else {
-            TriLogic::False
-        };
-        and_list.push_back(cube)
+    let term = if x > 0 {
+        TriLogic::True
+    } else {
+        TriLogic::False
+    };
+    for cube in F.iter_mut() {
+        cube.set(ind, term.clone());
     }
-    and_list
 }
 
 #[test]
 fn AND_test() {
-    let clist: CubeList = LinkedList::from_iter(vec![
+    let clist: CubeList = cube_list(vec![
         vec![
             TriLogic::DontCare,
             TriLogic::True,
@@ -346,26 +687,27 @@ fn AND_test() {
             TriLogic::False,
         ],
     ]);
-    let mut anda = AND(1, &clist);
+    let mut anda = clist.clone();
+    and_in_place(1, &mut anda);
     assert!(anda.len() == 2, "and_test list length check");
     assert!(
         anda.front().unwrap() ==
-            &[
+            &Cube::from(vec![
                 TriLogic::True,
                 TriLogic::True,
                 TriLogic::True,
                 TriLogic::DontCare,
-            ],
+            ]),
         "and_test 1st cube"
     );
     assert!(
         anda.pop_back() ==
-            Some(vec![
+            Some(Cube::from(vec![
                 TriLogic::True,
                 TriLogic::DontCare,
                 TriLogic::True,
                 TriLogic::False,
-            ]),
+            ])),
         "and_test 2nd cube"
     );
 }
@@ -378,7 +720,7 @@ fn OR(P: &CubeList, N: &CubeList) -> CubeList {
 
 #[test]
 fn OR_test() {
-    let clist1: CubeList = LinkedList::from_iter(vec![
+    let clist1: CubeList = cube_list(vec![
         vec![
             TriLogic::DontCare,
             TriLogic::True,
@@ -392,7 +734,7 @@ fn OR_test() {
             TriLogic::False,
         ],
     ]);
-    let clist2: CubeList = LinkedList::from_iter(vec![
+    let clist2: CubeList = cube_list(vec![
         vec![
             TriLogic::True,
             TriLogic::False,
@@ -419,13 +761,13 @@ fn direct_complement(F: &CubeList) -> CubeList {
     for i in 0..num_vars {
         match cube[i] {
             TriLogic::True => {
-                let mut new_cube = vec![TriLogic::DontCare; num_vars];
-                new_cube[i] = TriLogic::False;
+                let mut new_cube = Cube::dont_cares(num_vars);
+                new_cube.set(i, TriLogic::False);
                 result.push_back(new_cube);
             }
             TriLogic::False => {
-                let mut new_cube = vec![TriLogic::DontCare; num_vars];
-                new_cube[i] = TriLogic::True;
+                let mut new_cube = Cube::dont_cares(num_vars);
+                new_cube.set(i, TriLogic::True);
                 result.push_back(new_cube);
             }
             TriLogic::DontCare => {} // leave it as is
@@ -436,7 +778,7 @@ fn direct_complement(F: &CubeList) -> CubeList {
 
 #[test]
 fn direct_complement_test() {
-    let clist: CubeList = LinkedList::from_iter(vec![
+    let clist: CubeList = cube_list(vec![
         vec![
             TriLogic::DontCare,
             TriLogic::True,
@@ -452,34 +794,34 @@ fn direct_complement_test() {
             (0, c1) => {
                 assert!(
                     c1 ==
-                        &[
+                        &Cube::from(vec![
                             TriLogic::DontCare,
                             TriLogic::False,
                             TriLogic::DontCare,
                             TriLogic::DontCare,
-                        ]
+                        ])
                 )
             }
             (1, c2) => {
                 assert!(
                     c2 ==
-                        &[
+                        &Cube::from(vec![
                             TriLogic::DontCare,
                             TriLogic::DontCare,
                             TriLogic::True,
                             TriLogic::DontCare,
-                        ]
+                        ])
                 )
             }
             (2, c3) => {
                 assert!(
                     c3 ==
-                        &[
+                        &Cube::from(vec![
                             TriLogic::DontCare,
                             TriLogic::DontCare,
                             TriLogic::DontCare,
                             TriLogic::False,
-                        ]
+                        ])
                 )
             }
             _ => panic!("impossible happened"),
@@ -488,123 +830,2454 @@ fn direct_complement_test() {
     }
 }
 
-fn Complement(num_vars: usize, F: &CubeList) -> CubeList {
-    let F_is_simple = || -> (bool, CubeList) {
-        if F.len() == 0 {
-            // empty cube list
-            let mut clist: CubeList = LinkedList::new();
-            clist.push_back(vec![TriLogic::DontCare; num_vars]);
-            (true, clist)
-        } else if cubelist_contains_all_dont_cares_cube(F) {
-            (true, LinkedList::new())
-        } else if F.len() == 1 {
-            // cube list contains exactly one cube
-            (true, direct_complement(F))
-        } else {
-            (false, LinkedList::new())
-        }
-    };
+// the complement of the cover simple enough to be computed directly
+fn simple_complement(num_vars: usize, F: &CubeList) -> Option<CubeList> {
+    if F.is_empty() {
+        // empty cube list
+        let mut clist: CubeList = LinkedList::new();
+        clist.push_back(Cube::dont_cares(num_vars));
+        Some(clist)
+    } else if cubelist_contains_all_dont_cares_cube(F) {
+        Some(LinkedList::new())
+    } else if F.len() == 1 {
+        // cube list contains exactly one cube
+        Some(direct_complement(F))
+    } else {
+        None
+    }
+}
 
-    let check_simple = F_is_simple();
-    match check_simple {
-        (true, x) => x,
-        (false, _) => {
+fn Complement(num_vars: usize, F: &CubeList) -> CubeList {
+    match simple_complement(num_vars, F) {
+        Some(x) => x,
+        None => {
             // most binate variable for splitting
             let x = most_binate_variable(F);
             let mut P = Complement(num_vars, &positiveCofactor(F, x));
             let mut N = Complement(num_vars, &negativeCofactor(F, x));
             let and_ind = (x + 1) as isize;
-            P = AND(and_ind, &P);
-            N = AND(-and_ind, &N);
-            OR(&P, &N)
+            and_in_place(and_ind, &mut P);
+            and_in_place(-and_ind, &mut N);
+            // OR(&P, &N) without copying the cubes
+            P.append(&mut N);
+            P
         }
     }
 }
 
-fn WriteCubeList(fname: &str, F: &CubeList) -> Result<(), io::Error> {
-    let mut f = File::create(fname)?;
-    write!(f, "{}\n", F.front().unwrap().len())?; // num vars
-    write!(f, "{}\n", F.len())?; // num cubes
-    for cube in F.iter() {
-        // print number of not dont_care terms
-        let num_of_not_dont_cares = count_all_not_dont_cares(&*cube);
-        write!(f, "{}", num_of_not_dont_cares)?;
-        for i in 0..cube.len() {
-            match (*cube)[i] {
-                TriLogic::True => {
-                    write!(f, " {}", i + 1)?;
-                }
-                TriLogic::False => {
-                    write!(f, " {}", -(i as i32) + 1)?;
-                }
-                TriLogic::DontCare => {} // do nothing 
-            }
+// the cofactor branches are forked above these limits, smaller subproblems
+// are not worth the task overhead
+const PARALLEL_MAX_DEPTH: usize = 16;
+const PARALLEL_MIN_CUBES: usize = 32;
+
+// same as `Complement` with both cofactors complemented in parallel,
+// the cubes come in the same order as the sequential version gives
+fn ParallelComplement(num_vars: usize, F: &CubeList) -> CubeList {
+    parallel_complement(num_vars, F, 0)
+}
+
+fn parallel_complement(num_vars: usize, F: &CubeList, depth: usize) -> CubeList {
+    if depth >= PARALLEL_MAX_DEPTH || F.len() < PARALLEL_MIN_CUBES {
+        return Complement(num_vars, F);
+    }
+    match simple_complement(num_vars, F) {
+        Some(x) => x,
+        None => {
+            // most binate variable for splitting
+            let x = most_binate_variable(F);
+            let (mut P, mut N) = rayon::join(
+                || parallel_complement(num_vars, &positiveCofactor(F, x), depth + 1),
+                || parallel_complement(num_vars, &negativeCofactor(F, x), depth + 1),
+            );
+            let and_ind = (x + 1) as isize;
+            and_in_place(and_ind, &mut P);
+            and_in_place(-and_ind, &mut N);
+            P.append(&mut N);
+            P
         }
-        write!(f, "\n")?;
     }
-
-    Ok(())
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 3 {
-        println!("plz. gimme input and output fnames");
-        return;
+#[test]
+fn parallel_complement_test() {
+    for i in 1..6 {
+        let fname = format!("data/UnateRecursiveComplement/part{}.pcn", i);
+        let (num_vars, F) = read_pcn(&fname).unwrap();
+        assert!(
+            ParallelComplement(num_vars, &F) == Complement(num_vars, &F),
+            "{}: the same cubes in the same order",
+            fname
+        );
     }
 
-    let inp_fname = args[1].clone();
-    let out_fname = args[2].clone();
-    let is = File::open(inp_fname).unwrap();
-    let reader = BufReader::new(is);
-    let mut line_num = 0;
-    let mut num_vars: usize = 0;
-    let mut num_cubes: usize = 0;
-    let mut cube_list: Vec<Vec<TriLogic>> = Vec::new();
-
-    for maybe_line in reader.lines() {
-        let line = maybe_line.unwrap();
-        line_num += 1;
-        println!("<{}> : {}", line_num, line);
-        let chunks: Vec<&str> = line.split_terminator(|c: char| c.is_whitespace()).collect();
-        match line_num {
-            1 => num_vars = chunks[0].parse().ok().expect("a number expected"),
-            2 => {
-                num_cubes = chunks[0].parse().ok().expect("a number expected");
-                println!("num_vars: {} num_cubes: {}", num_vars, num_cubes);
-            }
-            _ => {
-                if (line_num - 2) > num_cubes {
-                    break;
-                }
-                let nterms: usize = chunks[0].parse().ok().expect("a number expected");
-                let terms: Vec<isize> = Vec::from_iter((0..nterms).map(|idx| {
-                    chunks[idx + 1].parse().ok().expect("a number expected")
-                }));
-                println!("nterms: {} terms: {:?}", nterms, terms);
-                let mut cube = vec![TriLogic::DontCare; num_vars];
-                for term in terms.iter() {
-                    if *term > 0 {
-                        cube[(*term - 1) as usize] = TriLogic::True;
-                    } else {
-                        cube[(-*term - 1) as usize] = TriLogic::False;
-                    }
-                }
-                cube_list.push(cube);
+    // a cover big enough to be split: all the minterms of 7 variables with odd parity
+    let num_vars = 7;
+    let F: CubeList = (0..1u32 << num_vars)
+        .filter(|m| m.count_ones() % 2 == 1)
+        .map(|m| {
+            let mut cube = Cube::dont_cares(num_vars);
+            for i in 0..num_vars {
+                cube.set(i, if m & (1 << i) != 0 {
+                    TriLogic::True
+                } else {
+                    TriLogic::False
+                });
             }
+            cube
+        })
+        .collect();
+    let compl = ParallelComplement(num_vars, &F);
+    assert!(compl == Complement(num_vars, &F), "deterministic ordering");
+    assert!(compl.len() == 64, "the even parity minterms");
+}
+
+// per variable (number of True literals, number of False literals) over the cover
+fn column_polarities(F: &CubeList) -> Vec<(usize, usize)> {
+    let num_vars = if let Some(c) = F.front() { c.len() } else { 0 };
+    let mut polarities = vec![(0, 0); num_vars];
+    for c in F.iter() {
+        for (i, term) in c.iter().enumerate() {
+            match term {
+                TriLogic::True => polarities[i].0 += 1,
+                TriLogic::False => polarities[i].1 += 1,
+                TriLogic::DontCare => {}
+            }
+        }
+    }
+    polarities
+}
+
+fn Tautology(F: &CubeList) -> bool {
+    if F.is_empty() {
+        // empty cube list is the constant 0
+        return false;
+    }
+    if cubelist_contains_all_dont_cares_cube(F) {
+        return true;
+    }
+    if F.len() == 1 {
+        // the only cube has at least one literal
+        return false;
+    }
+
+    let polarities = column_polarities(F);
+    let is_unate = |&(t, c): &(usize, usize)| t == 0 || c == 0;
+    if polarities.iter().all(is_unate) {
+        // unate cover is a tautology iff it contains the all dont cares cube
+        return false;
+    }
+
+    // unate reduction: cubes depending on a unate variable can be dropped,
+    // F is a tautology iff the rest of cubes is a tautology
+    let unate_vars: Vec<usize> = polarities.iter()
+        .enumerate()
+        .filter(|&(_, p)| is_unate(p) && *p != (0, 0))
+        .map(|(i, _)| i)
+        .collect();
+    if !unate_vars.is_empty() {
+        let reduced: CubeList = F.iter()
+            .filter(|c| unate_vars.iter().all(|&i| c[i] == TriLogic::DontCare))
+            .cloned()
+            .collect();
+        return Tautology(&reduced);
+    }
+
+    // most binate variable for splitting
+    let x = most_binate_variable(F);
+    Tautology(&positiveCofactor(F, x)) && Tautology(&negativeCofactor(F, x))
+}
+
+#[test]
+fn tautology_test() {
+    let x_or_not_x: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::False, TriLogic::DontCare],
+    ]);
+    assert!(Tautology(&x_or_not_x), "case0: a + a'");
+
+    let unate: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::DontCare, TriLogic::True],
+    ]);
+    assert!(!Tautology(&unate), "case1: a + b");
+
+    let all_minterms: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::True],
+        vec![TriLogic::True, TriLogic::False],
+        vec![TriLogic::False, TriLogic::True],
+        vec![TriLogic::False, TriLogic::False],
+    ]);
+    assert!(Tautology(&all_minterms), "case2: ab + ab' + a'b + a'b'");
+
+    let reducible: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::DontCare, TriLogic::True],
+        vec![TriLogic::False, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::DontCare, TriLogic::False, TriLogic::DontCare],
+    ]);
+    assert!(!Tautology(&reducible), "case3: ac + a'b + b'");
+
+    assert!(!Tautology(&LinkedList::new()), "case4: empty cubelist");
+    assert!(
+        Tautology(&Complement(3, &LinkedList::new())),
+        "case5: complement of empty cubelist"
+    );
+}
+
+// `a` contains `b` iff every literal of `a` is also a literal of `b`
+fn cube_contains(a: &Cube, b: &Cube) -> bool {
+    a.contains(b)
+}
+
+// cofactor of the cover with respect to the cube `c`,
+// cubes not intersecting `c` are removed, literals of `c` are turned into dont cares
+fn cube_cofactor(F: &CubeList, c: &Cube) -> CubeList {
+    F.iter().filter_map(|f| f.cofactor(c)).collect()
+}
+
+// the cube is covered by F iff the cofactor of F w.r.t. the cube is a tautology
+fn cover_contains_cube(F: &CubeList, c: &Cube) -> bool {
+    Tautology(&cube_cofactor(F, c))
+}
+
+// F implies G iff every cube of F is covered by G
+fn cover_implies(F: &CubeList, G: &CubeList) -> bool {
+    F.iter().all(|c| cover_contains_cube(G, c))
+}
+
+#[test]
+fn containment_test() {
+    assert!(
+        cube_contains(
+            &Cube::from(vec![TriLogic::True, TriLogic::DontCare, TriLogic::DontCare]),
+            &Cube::from(vec![TriLogic::True, TriLogic::False, TriLogic::DontCare]),
+        ),
+        "a contains ab'"
+    );
+    assert!(
+        !cube_contains(
+            &Cube::from(vec![TriLogic::True, TriLogic::False, TriLogic::DontCare]),
+            &Cube::from(vec![TriLogic::True, TriLogic::DontCare, TriLogic::DontCare]),
+        ),
+        "ab' does not contain a"
+    );
+
+    // F = ab + a'c
+    let F: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::False, TriLogic::DontCare, TriLogic::True],
+    ]);
+    // bc is the consensus term of F, covered by F though no single cube contains it
+    assert!(
+        cover_contains_cube(
+            &F,
+            &Cube::from(vec![TriLogic::DontCare, TriLogic::True, TriLogic::True]),
+        ),
+        "F covers bc"
+    );
+    assert!(
+        !cover_contains_cube(
+            &F,
+            &Cube::from(vec![TriLogic::DontCare, TriLogic::True, TriLogic::DontCare]),
+        ),
+        "F does not cover b"
+    );
+
+    // G = b + c
+    let G: CubeList = cube_list(vec![
+        vec![TriLogic::DontCare, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::DontCare, TriLogic::DontCare, TriLogic::True],
+    ]);
+    assert!(cover_implies(&F, &G), "F implies G");
+    assert!(!cover_implies(&G, &F), "G does not imply F");
+
+    let compl = Complement(3, &F);
+    assert!(
+        cover_implies(&OR(&F, &compl), &Complement(3, &LinkedList::new())) &&
+            Tautology(&OR(&F, &compl)),
+        "F + F' is a tautology"
+    );
+}
+
+// the literals of the cube in PCN: variable i is i + 1 and -(i + 1) if complemented
+fn pcn_literals(cube: &Cube) -> Vec<isize> {
+    cube.iter()
+        .enumerate()
+        .filter_map(|(i, term)| match term {
+            TriLogic::True => Some(i as isize + 1),
+            TriLogic::False => Some(-(i as isize + 1)),
+            TriLogic::DontCare => None,
+        })
+        .collect()
+}
+
+// cubes sorted by their literals, so that the same cover is always written the same way
+fn canonical_order(F: &CubeList) -> Vec<Cube> {
+    let mut cubes: Vec<Cube> = F.iter().cloned().collect();
+    cubes.sort_by_cached_key(pcn_literals);
+    cubes
+}
+
+// the number of variables is given explicitly as the cover may be empty
+fn write_pcn<W: Write>(w: &mut W, num_vars: usize, F: &CubeList) -> Result<(), io::Error> {
+    writeln!(w, "{}", num_vars)?;
+    writeln!(w, "{}", F.len())?;
+    for cube in canonical_order(F).iter() {
+        let literals = pcn_literals(cube);
+        write!(w, "{}", literals.len())?;
+        for literal in literals.iter() {
+            write!(w, " {}", literal)?;
         }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn write_pcn_test() {
+    let round_trip = |num_vars: usize, F: &CubeList| -> (usize, CubeList) {
+        let mut out: Vec<u8> = Vec::new();
+        write_pcn(&mut out, num_vars, F).unwrap();
+        parse_pcn(&out[..]).unwrap()
+    };
+
+    let (num_vars, F) = read_pcn("data/UnateRecursiveComplement/part1.pcn").unwrap();
+    let compl = Complement(num_vars, &F);
+    let (nv, G) = round_trip(num_vars, &compl);
+    assert!(nv == 5 && G.len() == compl.len(), "the header");
+    assert!(G.iter().collect::<Vec<_>>() == canonical_order(&compl).iter().collect::<Vec<_>>());
+
+    let (nv, G) = round_trip(4, &LinkedList::new());
+    assert!(nv == 4 && G.is_empty(), "empty cover");
+    let (nv, G) = round_trip(4, &Complement(4, &LinkedList::new()));
+    assert!(nv == 4 && cubelist_contains_all_dont_cares_cube(&G), "all dont cares cube");
+
+    let mut out: Vec<u8> = Vec::new();
+    write_pcn(&mut out, 5, &F).unwrap();
+    assert!(
+        String::from_utf8(out).unwrap() == "5\n3\n2 -1 5\n3 1 -3 -4\n3 2 3 4\n",
+        "canonical order of part1.pcn"
+    );
+}
+
+// cubes are disjoint iff there is a variable with opposite literals
+fn cubes_intersect(a: &Cube, b: &Cube) -> bool {
+    a.intersects(b)
+}
+
+fn supercube(F: &CubeList) -> Option<Cube> {
+    let mut cubes = F.iter();
+    let mut sc = cubes.next()?.clone();
+    for c in cubes {
+        sc.supercube_with(c);
     }
-    println!("cube_list: {:?}", cube_list);
-    let compl = Complement(num_vars, &LinkedList::from_iter(cube_list));
-    println!("compl: {:?}", compl);
+    Some(sc)
+}
+
+#[allow(dead_code)]
+fn cube_intersection(a: &Cube, b: &Cube) -> Option<Cube> {
+    a.intersection(b)
+}
 
-    // print it in binary form
-    for cube in compl.iter() {
-        print!("[ ");
-        for term in cube.iter() {
-            print!("{:02b} ", (*term).clone() as usize);
+// drops the cubes contained in another cube of the cover (and duplicates)
+fn single_cube_containment(F: &CubeList) -> CubeList {
+    // bigger cubes first, so that a cube may only be contained by the kept ones
+    let mut cubes: Vec<Cube> = F.iter().cloned().collect();
+    cubes.sort_by_key(count_all_not_dont_cares);
+    let mut kept: Vec<Cube> = Vec::with_capacity(cubes.len());
+    for c in cubes.into_iter() {
+        if !kept.iter().any(|k| k.contains(&c)) {
+            kept.push(c);
         }
-        println!("]");
     }
-    WriteCubeList(&out_fname, &compl);
+    LinkedList::from_iter(kept)
+}
+
+// OR followed by the single cube containment cleanup
+#[allow(dead_code)]
+fn UNION(F: &CubeList, G: &CubeList) -> CubeList {
+    single_cube_containment(&OR(F, G))
+}
+
+// cover-with-cover AND: pairwise intersections of the cubes
+#[allow(dead_code)]
+fn INTERSECT(F: &CubeList, G: &CubeList) -> CubeList {
+    let mut and_list: CubeList = LinkedList::new();
+    for f in F.iter() {
+        for g in G.iter() {
+            if let Some(c) = f.intersection(g) {
+                and_list.push_back(c);
+            }
+        }
+    }
+    single_cube_containment(&and_list)
+}
+
+// F # G: the minterms of F not covered by G
+#[allow(dead_code)]
+fn SHARP(F: &CubeList, G: &CubeList) -> CubeList {
+    let mut result = F.clone();
+    for g in G.iter() {
+        let sharped: CubeList = result.iter().flat_map(|f| f.sharp(g)).collect();
+        result = single_cube_containment(&sharped);
+    }
+    result
+}
+
+// F #d G: same as `SHARP` but the result cubes are pairwise disjoint
+// provided the cubes of F are
+#[allow(dead_code)]
+fn DISJOINT_SHARP(F: &CubeList, G: &CubeList) -> CubeList {
+    let mut result = F.clone();
+    for g in G.iter() {
+        result = result.iter().flat_map(|f| f.disjoint_sharp(g)).collect();
+    }
+    result
+}
+
+#[test]
+fn boolean_operations_test() {
+    // F = ab + a'c, G = b + c
+    let F: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::False, TriLogic::DontCare, TriLogic::True],
+    ]);
+    let G: CubeList = cube_list(vec![
+        vec![TriLogic::DontCare, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::DontCare, TriLogic::DontCare, TriLogic::True],
+    ]);
+    let equivalent = |a: &CubeList, b: &CubeList| cover_implies(a, b) && cover_implies(b, a);
+
+    assert!(
+        cube_intersection(F.front().unwrap(), F.back().unwrap()).is_none(),
+        "ab and a'c are disjoint"
+    );
+    assert!(equivalent(&INTERSECT(&F, &G), &F), "F implies G, so F G == F");
+
+    // the universe sharp F is the complement of F
+    let universe = Complement(3, &LinkedList::new());
+    let compl = Complement(3, &F);
+    assert!(equivalent(&SHARP(&universe, &F), &compl), "1 # F == F'");
+    let dsharp = DISJOINT_SHARP(&universe, &F);
+    assert!(equivalent(&dsharp, &compl), "1 #d F == F'");
+    for (i, a) in dsharp.iter().enumerate() {
+        for b in dsharp.iter().skip(i + 1) {
+            assert!(!cubes_intersect(a, b), "disjoint sharp cubes are disjoint");
+        }
+    }
+    assert!(SHARP(&F, &G).is_empty(), "F # G is empty as F implies G");
+    let g_minus_f = SHARP(&G, &F);
+    assert!(equivalent(&g_minus_f, &INTERSECT(&G, &compl)), "G # F == G F'");
+
+    // cleanup after OR
+    let union = UNION(&F, &G);
+    assert!(union.len() == 2 && equivalent(&union, &G), "F + G == G");
+    assert!(OR(&F, &G).len() == 4);
+
+    // generalized cofactor w.r.t. the cube a
+    let a = Cube::from(vec![TriLogic::True, TriLogic::DontCare, TriLogic::DontCare]);
+    let Fa = cube_cofactor(&F, &a);
+    assert!(
+        Fa.len() == 1 &&
+            *Fa.front().unwrap() == Cube::from(vec![TriLogic::DontCare, TriLogic::True, TriLogic::DontCare]),
+        "F_a == b"
+    );
+}
+
+// cost of the cover for the minimization loop: number of cubes, then number of literals
+fn cover_cost(F: &CubeList) -> (usize, usize) {
+    (F.len(), F.iter().map(count_all_not_dont_cares).sum())
+}
+
+// EXPAND: raise literals of every cube to dont cares while the cube stays
+// disjoint from the off-set R, then drop cubes covered by the expanded ones
+fn expand(F: &CubeList, R: &CubeList) -> CubeList {
+    // expand the largest cubes first, they are most likely to cover the others
+    let mut cubes: Vec<Cube> = F.iter().cloned().collect();
+    cubes.sort_by_key(count_all_not_dont_cares);
+
+    let mut expanded: Vec<Cube> = Vec::with_capacity(cubes.len());
+    for c in cubes.iter() {
+        if expanded.iter().any(|e| cube_contains(e, c)) {
+            continue;
+        }
+        let mut cube = c.clone();
+        for i in 0..cube.len() {
+            if cube[i] == TriLogic::DontCare {
+                continue;
+            }
+            let literal = cube[i].clone();
+            cube.set(i, TriLogic::DontCare);
+            if R.iter().any(|r| cubes_intersect(&cube, r)) {
+                cube.set(i, literal);
+            }
+        }
+        expanded.retain(|e| !cube_contains(&cube, e));
+        expanded.push(cube);
+    }
+    LinkedList::from_iter(expanded)
+}
+
+// IRREDUNDANT: drop cubes covered by the rest of the cover and the dont care set D
+fn irredundant(F: &CubeList, D: &CubeList) -> CubeList {
+    let mut cubes: Vec<Cube> = F.iter().cloned().collect();
+    let mut i = 0;
+    while i < cubes.len() {
+        let rest: CubeList = cubes.iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, c)| c.clone())
+            .chain(D.iter().cloned())
+            .collect();
+        if cover_contains_cube(&rest, &cubes[i]) {
+            cubes.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    LinkedList::from_iter(cubes)
+}
+
+// REDUCE: shrink every cube to the supercube of the minterms covered by it only,
+// minterms of the dont care set D need no cover
+fn reduce(num_vars: usize, F: &CubeList, D: &CubeList) -> CubeList {
+    let mut cubes: Vec<Cube> = F.iter().cloned().collect();
+    let mut i = 0;
+    while i < cubes.len() {
+        let rest: CubeList = cubes.iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, c)| c.clone())
+            .chain(D.iter().cloned())
+            .collect();
+        let uncovered = Complement(num_vars, &cube_cofactor(&rest, &cubes[i]));
+        match supercube(&uncovered).and_then(|sc| sc.intersection(&cubes[i])) {
+            None => {
+                // the cube is redundant
+                cubes.remove(i);
+            }
+            Some(reduced) => {
+                cubes[i] = reduced;
+                i += 1;
+            }
+        }
+    }
+    LinkedList::from_iter(cubes)
+}
+
+// heuristic two-level minimization in the espresso style:
+// iterate EXPAND, IRREDUNDANT and REDUCE while the cost improves
+#[cfg(test)]
+fn Minimize(num_vars: usize, F: &CubeList) -> CubeList {
+    minimize_with_dont_cares(num_vars, F, &LinkedList::new())
+}
+
+// minimization of the incompletely specified function with the on-set F
+// and the dont care set D, the off-set is the complement of F + D
+fn minimize_with_dont_cares(num_vars: usize, F: &CubeList, D: &CubeList) -> CubeList {
+    let R = Complement(num_vars, &OR(F, D));
+    let mut best = irredundant(&expand(F, &R), D);
+    let mut best_cost = cover_cost(&best);
+    loop {
+        let reduced = reduce(num_vars, &best, D);
+        let candidate = irredundant(&expand(&reduced, &R), D);
+        let cost = cover_cost(&candidate);
+        if cost < best_cost {
+            best = candidate;
+            best_cost = cost;
+        } else {
+            break;
+        }
+    }
+    best
+}
+
+#[test]
+fn minimize_test() {
+    // ab + ab' + a'b == a + b
+    let F: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::True],
+        vec![TriLogic::True, TriLogic::False],
+        vec![TriLogic::False, TriLogic::True],
+    ]);
+    let min = Minimize(2, &F);
+    assert!(min.len() == 2, "a + b has two cubes");
+    assert!(cover_cost(&min) == (2, 2), "a + b has two literals");
+    assert!(cover_implies(&min, &F) && cover_implies(&F, &min), "a + b equivalence");
+
+    // the complement of part1.pcn: bcd + a'e + ac'd'
+    let part1: CubeList = cube_list(vec![
+        vec![TriLogic::DontCare, TriLogic::True, TriLogic::True, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::False, TriLogic::DontCare, TriLogic::DontCare, TriLogic::DontCare, TriLogic::True],
+        vec![TriLogic::True, TriLogic::DontCare, TriLogic::False, TriLogic::False, TriLogic::DontCare],
+    ]);
+    let compl = Complement(5, &part1);
+    let min = Minimize(5, &compl);
+    assert!(min.len() <= compl.len(), "minimized cover is not larger");
+    assert!(
+        cover_implies(&min, &compl) && cover_implies(&compl, &min),
+        "minimized complement equivalence"
+    );
+    assert!(!cubelist_contains_all_dont_cares_cube(&min));
+    assert!(min.iter().all(|c| !cover_contains_cube(&part1, c) ||
+                            count_all_not_dont_cares(c) == 0));
+}
+
+#[derive(Debug)]
+enum PcnError {
+    Io(io::Error),
+    // the header or a cube line ended before the expected number
+    MissingNumber { line: usize, what: &'static str },
+    InvalidNumber { line: usize, column: usize, token: String },
+    LiteralOutOfRange { line: usize, column: usize, literal: isize, num_vars: usize },
+    ConflictingLiteral { line: usize, column: usize, literal: isize },
+    TermCountMismatch { line: usize, expected: usize, found: usize },
+    // anything after the number of a header line
+    UnexpectedToken { line: usize, column: usize, token: String },
+    CubeCountMismatch { expected: usize, found: usize },
+    TrailingCube { line: usize },
+}
+
+impl fmt::Display for PcnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PcnError::Io(ref e) => write!(f, "{}", e),
+            PcnError::MissingNumber { line, what } => write!(f, "line {}: {} expected", line, what),
+            PcnError::InvalidNumber { line, column, ref token } => {
+                write!(f, "line {}, column {}: a number expected, found `{}`", line, column, token)
+            }
+            PcnError::LiteralOutOfRange { line, column, literal, num_vars } => write!(
+                f,
+                "line {}, column {}: literal {} out of range for {} variables",
+                line,
+                column,
+                literal,
+                num_vars
+            ),
+            PcnError::ConflictingLiteral { line, column, literal } => write!(
+                f,
+                "line {}, column {}: literal {} repeats or contradicts a previous one",
+                line,
+                column,
+                literal
+            ),
+            PcnError::TermCountMismatch { line, expected, found } => write!(
+                f,
+                "line {}: {} literals declared but {} found",
+                line,
+                expected,
+                found
+            ),
+            PcnError::UnexpectedToken { line, column, ref token } => {
+                write!(f, "line {}, column {}: unexpected `{}` after the count", line, column, token)
+            }
+            PcnError::CubeCountMismatch { expected, found } => {
+                write!(f, "{} cubes declared but {} found", expected, found)
+            }
+            PcnError::TrailingCube { line } => {
+                write!(f, "line {}: more cubes than declared", line)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for PcnError {
+    fn from(e: io::Error) -> PcnError {
+        PcnError::Io(e)
+    }
+}
+
+// whitespace separated tokens with their 1-based columns, `#` starts a comment
+fn tokens_with_columns(line: &str) -> Vec<(usize, &str)> {
+    let line = line.split('#').next().unwrap();
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, ch) in line.char_indices() {
+        match (ch.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                tokens.push((s, &line[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((s, &line[s..]));
+    }
+    tokens.into_iter()
+        .map(|(i, token)| (line[..i].chars().count() + 1, token))
+        .collect()
+}
+
+// positional cube notation: the number of variables, the number of cubes,
+// then a line per cube with the number of literals followed by the literals,
+// a negative literal is a complemented variable
+fn parse_pcn<R: BufRead>(reader: R) -> Result<(usize, CubeList), PcnError> {
+    let mut num_vars: Option<usize> = None;
+    let mut num_cubes: Option<usize> = None;
+    let mut cube_list: CubeList = LinkedList::new();
+
+    for (idx, maybe_line) in reader.lines().enumerate() {
+        let line = maybe_line?;
+        let line_num = idx + 1;
+        let tokens = tokens_with_columns(&line);
+        if tokens.is_empty() {
+            continue;
+        }
+        let number = |(column, token): (usize, &str)| -> Result<isize, PcnError> {
+            token.parse().map_err(|_| PcnError::InvalidNumber {
+                line: line_num,
+                column,
+                token: String::from(token),
+            })
+        };
+        let count = |(column, token): (usize, &str)| -> Result<usize, PcnError> {
+            token.parse().map_err(|_| PcnError::InvalidNumber {
+                line: line_num,
+                column,
+                token: String::from(token),
+            })
+        };
+
+        // the header lines hold the count alone
+        let single = |tokens: &[(usize, &str)]| -> Result<usize, PcnError> {
+            if let Some(&(column, token)) = tokens.get(1) {
+                return Err(PcnError::UnexpectedToken {
+                    line: line_num,
+                    column,
+                    token: String::from(token),
+                });
+            }
+            count(tokens[0])
+        };
+
+        let (nv, nc) = match (num_vars, num_cubes) {
+            (None, _) => {
+                num_vars = Some(single(&tokens)?);
+                continue;
+            }
+            (Some(_), None) => {
+                num_cubes = Some(single(&tokens)?);
+                continue;
+            }
+            (Some(nv), Some(nc)) => (nv, nc),
+        };
+        if cube_list.len() == nc {
+            return Err(PcnError::TrailingCube { line: line_num });
+        }
+
+        let nterms = count(tokens[0])?;
+        if tokens.len() - 1 != nterms {
+            return Err(PcnError::TermCountMismatch {
+                line: line_num,
+                expected: nterms,
+                found: tokens.len() - 1,
+            });
+        }
+        let mut cube = Cube::dont_cares(nv);
+        for &(column, token) in tokens[1..].iter() {
+            let literal = number((column, token))?;
+            let var = literal.unsigned_abs();
+            if var == 0 || var > nv {
+                return Err(PcnError::LiteralOutOfRange {
+                    line: line_num,
+                    column,
+                    literal,
+                    num_vars: nv,
+                });
+            }
+            if cube[var - 1] != TriLogic::DontCare {
+                return Err(PcnError::ConflictingLiteral {
+                    line: line_num,
+                    column,
+                    literal,
+                });
+            }
+            cube.set(var - 1, if literal > 0 {
+                TriLogic::True
+            } else {
+                TriLogic::False
+            });
+        }
+        cube_list.push_back(cube);
+    }
+
+    match (num_vars, num_cubes) {
+        (None, _) => Err(PcnError::MissingNumber {
+            line: 1,
+            what: "number of variables",
+        }),
+        (Some(_), None) => Err(PcnError::MissingNumber {
+            line: 2,
+            what: "number of cubes",
+        }),
+        (Some(nv), Some(nc)) => {
+            if cube_list.len() != nc {
+                Err(PcnError::CubeCountMismatch {
+                    expected: nc,
+                    found: cube_list.len(),
+                })
+            } else {
+                Ok((nv, cube_list))
+            }
+        }
+    }
+}
+
+fn read_pcn(fname: &str) -> Result<(usize, CubeList), PcnError> {
+    let is = File::open(fname)?;
+    parse_pcn(BufReader::new(is))
+}
+
+#[test]
+fn parse_pcn_test() {
+    for i in 1..6 {
+        let fname = format!("data/UnateRecursiveComplement/part{}.pcn", i);
+        assert!(read_pcn(&fname).is_ok(), "{} is well formed", fname);
+    }
+
+    let (num_vars, F) = read_pcn("data/UnateRecursiveComplement/comments.pcn").unwrap();
+    assert!(num_vars == 5 && F.len() == 3, "comments and blank lines are skipped");
+
+    macro_rules! assert_malformed {
+        ($fname:expr, $pattern:pat) => {
+            let path = format!("data/UnateRecursiveComplement/malformed/{}", $fname);
+            match read_pcn(&path) {
+                Err($pattern) => {}
+                other => panic!("{}: unexpected {:?}", path, other),
+            }
+        };
+    }
+    assert_malformed!("empty.pcn", PcnError::MissingNumber { line: 1, .. });
+    assert_malformed!("no_cube_count.pcn", PcnError::MissingNumber { line: 2, .. });
+    assert_malformed!("bad_number.pcn", PcnError::InvalidNumber { line: 3, column: 5, .. });
+    assert_malformed!(
+        "literal_out_of_range.pcn",
+        PcnError::LiteralOutOfRange { line: 4, column: 6, literal: -6, num_vars: 5 }
+    );
+    assert_malformed!("zero_literal.pcn", PcnError::LiteralOutOfRange { literal: 0, .. });
+    assert_malformed!(
+        "conflicting_literal.pcn",
+        PcnError::ConflictingLiteral { line: 3, column: 5, literal: -2 }
+    );
+    assert_malformed!(
+        "term_count.pcn",
+        PcnError::TermCountMismatch { line: 5, expected: 3, found: 2 }
+    );
+    assert_malformed!(
+        "missing_cubes.pcn",
+        PcnError::CubeCountMismatch { expected: 3, found: 2 }
+    );
+    assert_malformed!("trailing_cube.pcn", PcnError::TrailingCube { line: 5 });
+    assert_malformed!("header_tokens.pcn", PcnError::UnexpectedToken { line: 2, column: 3, .. });
+
+    // the most negative literal is out of range rather than an overflow
+    match parse_pcn(format!("2\n1\n1 {}\n", isize::MIN).as_bytes()) {
+        Err(PcnError::LiteralOutOfRange { literal: isize::MIN, .. }) => {}
+        other => panic!("isize::MIN: unexpected {:?}", other),
+    }
+}
+
+// the output part of a multi-output cube, one bit per output
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct OutputMask {
+    words: Vec<u64>,
+}
+
+impl OutputMask {
+    fn empty(num_outputs: usize) -> OutputMask {
+        OutputMask { words: vec![0; num_outputs.div_ceil(64)] }
+    }
+
+    fn full(num_outputs: usize) -> OutputMask {
+        let mut mask = OutputMask::empty(num_outputs);
+        for j in 0..num_outputs {
+            mask.set(j);
+        }
+        mask
+    }
+
+    fn single(num_outputs: usize, j: usize) -> OutputMask {
+        let mut mask = OutputMask::empty(num_outputs);
+        mask.set(j);
+        mask
+    }
+
+    fn set(&mut self, j: usize) {
+        self.words[j / 64] |= 1 << (j % 64);
+    }
+
+    fn get(&self, j: usize) -> bool {
+        self.words[j / 64] & (1 << (j % 64)) != 0
+    }
+
+    fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    fn and(&self, other: &OutputMask) -> OutputMask {
+        OutputMask {
+            words: self.words.iter().zip(other.words.iter()).map(|(&a, &b)| a & b).collect(),
+        }
+    }
+
+    fn or_assign(&mut self, other: &OutputMask) {
+        for (a, b) in self.words.iter_mut().zip(other.words.iter()) {
+            *a |= *b;
+        }
+    }
+
+    fn ones<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
+        (0..self.words.len() * 64).filter(move |&j| self.get(j))
+    }
+}
+
+// a cube of the multi-output cover: the input part and the outputs it belongs to
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct MultiOutputCube {
+    inputs: Cube,
+    outputs: OutputMask,
+}
+
+type MultiOutputCover = LinkedList<MultiOutputCube>;
+
+// joins per output covers into one multi-output cover
+fn multi_output_cover(num_outputs: usize, covers: &[CubeList]) -> MultiOutputCover {
+    let cover = covers.iter()
+        .enumerate()
+        .flat_map(|(j, F)| {
+            F.iter().map(move |c| MultiOutputCube {
+                inputs: c.clone(),
+                outputs: OutputMask::single(num_outputs, j),
+            })
+        })
+        .collect();
+    merge_output_parts(cover)
+}
+
+// splits the multi-output cover into a cover per output
+fn split_outputs(num_outputs: usize, F: &MultiOutputCover) -> Vec<CubeList> {
+    (0..num_outputs)
+        .map(|j| {
+            F.iter()
+                .filter(|c| c.outputs.get(j))
+                .map(|c| c.inputs.clone())
+                .collect()
+        })
+        .collect()
+}
+
+// cubes with the same input part are shared by their outputs
+fn merge_output_parts(F: MultiOutputCover) -> MultiOutputCover {
+    let mut merged: Vec<MultiOutputCube> = Vec::with_capacity(F.len());
+    let mut index_of: HashMap<Cube, usize> = HashMap::new();
+    for c in F.into_iter() {
+        if let Some(&i) = index_of.get(&c.inputs) {
+            merged[i].outputs.or_assign(&c.outputs);
+        } else {
+            index_of.insert(c.inputs.clone(), merged.len());
+            merged.push(c);
+        }
+    }
+    LinkedList::from_iter(merged)
+}
+
+fn multi_output_cofactor(F: &MultiOutputCover, x: usize, term: TriLogic) -> MultiOutputCover {
+    F.iter()
+        .filter(|c| c.inputs[x] == TriLogic::DontCare || c.inputs[x] == term)
+        .map(|c| {
+            let mut cube = c.clone();
+            cube.inputs.set(x, TriLogic::DontCare);
+            cube
+        })
+        .collect()
+}
+
+// unate recursive complement of every output of F at once: outputs with a simple
+// cover are complemented directly, the rest share the splitting variable and
+// the recursion, identical input cubes of the results are merged
+fn MultiOutputComplement(num_vars: usize, num_outputs: usize, F: &MultiOutputCover) -> MultiOutputCover {
+    multi_output_complement(num_vars, num_outputs, F, &OutputMask::full(num_outputs))
+}
+
+fn multi_output_complement(
+    num_vars: usize,
+    num_outputs: usize,
+    F: &MultiOutputCover,
+    active: &OutputMask,
+) -> MultiOutputCover {
+    let mut result: MultiOutputCover = LinkedList::new();
+    let mut remaining = OutputMask::empty(num_outputs);
+    for j in active.ones() {
+        let Fj: CubeList = F.iter()
+            .filter(|c| c.outputs.get(j))
+            .map(|c| c.inputs.clone())
+            .collect();
+        if Fj.len() <= 1 || cubelist_contains_all_dont_cares_cube(&Fj) {
+            for c in Complement(num_vars, &Fj).into_iter() {
+                result.push_back(MultiOutputCube {
+                    inputs: c,
+                    outputs: OutputMask::single(num_outputs, j),
+                });
+            }
+        } else {
+            remaining.set(j);
+        }
+    }
+
+    if !remaining.is_empty() {
+        let G: MultiOutputCover = F.iter()
+            .map(|c| MultiOutputCube {
+                inputs: c.inputs.clone(),
+                outputs: c.outputs.and(&remaining),
+            })
+            .filter(|c| !c.outputs.is_empty())
+            .collect();
+        // most binate variable over the input parts of the outputs left
+        let inputs: CubeList = G.iter().map(|c| c.inputs.clone()).collect();
+        let x = most_binate_variable(&inputs);
+        for term in [TriLogic::True, TriLogic::False].iter() {
+            let cofactor = multi_output_cofactor(&G, x, term.clone());
+            let mut branch = multi_output_complement(num_vars, num_outputs, &cofactor, &remaining);
+            for c in branch.iter_mut() {
+                c.inputs.set(x, term.clone());
+            }
+            result.append(&mut branch);
+        }
+    }
+    merge_output_parts(result)
+}
+
+#[test]
+fn multi_output_complement_test() {
+    // f = ab + a'c, g = ab + c', h = 0
+    let f = cube_list(vec![
+        vec![TriLogic::True, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::False, TriLogic::DontCare, TriLogic::True],
+    ]);
+    let g = cube_list(vec![
+        vec![TriLogic::True, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::DontCare, TriLogic::DontCare, TriLogic::False],
+    ]);
+    let h: CubeList = LinkedList::new();
+    let covers = vec![f, g, h];
+    let F = multi_output_cover(3, &covers);
+    assert!(F.len() == 3, "ab is shared by f and g");
+
+    let compl = MultiOutputComplement(3, 3, &F);
+    let per_output = split_outputs(3, &compl);
+    let mut separate_cubes = 0;
+    for j in 0..3 {
+        let expected = Complement(3, &covers[j]);
+        separate_cubes += expected.len();
+        assert!(
+            cover_implies(&per_output[j], &expected) && cover_implies(&expected, &per_output[j]),
+            "output {} complement",
+            j
+        );
+        assert!(
+            per_output[j].iter().all(|c| covers[j].iter().all(|f| !cubes_intersect(c, f))),
+            "output {} complement is disjoint from the output",
+            j
+        );
+    }
+    assert!(compl.len() <= separate_cubes, "cubes are shared across outputs");
+}
+
+// reduced ordered BDD, variables are ordered by their index
+type BddRef = usize;
+
+const BDD_FALSE: BddRef = 0;
+const BDD_TRUE: BddRef = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum BddOp {
+    And,
+    Or,
+    Xor,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BddNode {
+    var: usize,
+    low: BddRef,
+    high: BddRef,
+}
+
+struct BddManager {
+    nodes: Vec<BddNode>,
+    // (var, low, high) -> node, keeps the diagram reduced
+    unique: HashMap<(usize, BddRef, BddRef), BddRef>,
+    // memoized results of `apply`
+    computed: HashMap<(BddOp, BddRef, BddRef), BddRef>,
+}
+
+impl BddManager {
+    fn new(num_vars: usize) -> BddManager {
+        // terminals are below all the variables
+        let terminal = |_| BddNode {
+            var: num_vars,
+            low: BDD_FALSE,
+            high: BDD_FALSE,
+        };
+        BddManager {
+            nodes: (0..2).map(terminal).collect(),
+            unique: HashMap::new(),
+            computed: HashMap::new(),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn mk(&mut self, var: usize, low: BddRef, high: BddRef) -> BddRef {
+        if low == high {
+            return low;
+        }
+        if let Some(&f) = self.unique.get(&(var, low, high)) {
+            return f;
+        }
+        let f = self.nodes.len();
+        self.nodes.push(BddNode {
+            var,
+            low,
+            high,
+        });
+        self.unique.insert((var, low, high), f);
+        f
+    }
+
+    #[allow(dead_code)]
+    fn var(&mut self, i: usize) -> BddRef {
+        self.mk(i, BDD_FALSE, BDD_TRUE)
+    }
+
+    fn apply(&mut self, op: BddOp, f: BddRef, g: BddRef) -> BddRef {
+        // terminal cases
+        match op {
+            BddOp::And => {
+                if f == BDD_FALSE || g == BDD_FALSE {
+                    return BDD_FALSE;
+                }
+                if f == BDD_TRUE || f == g {
+                    return g;
+                }
+                if g == BDD_TRUE {
+                    return f;
+                }
+            }
+            BddOp::Or => {
+                if f == BDD_TRUE || g == BDD_TRUE {
+                    return BDD_TRUE;
+                }
+                if f == BDD_FALSE || f == g {
+                    return g;
+                }
+                if g == BDD_FALSE {
+                    return f;
+                }
+            }
+            BddOp::Xor => {
+                if f == g {
+                    return BDD_FALSE;
+                }
+                if f == BDD_FALSE {
+                    return g;
+                }
+                if g == BDD_FALSE {
+                    return f;
+                }
+                if f == BDD_TRUE && g == BDD_TRUE {
+                    return BDD_FALSE;
+                }
+            }
+        }
+        // the operations are commutative
+        let key = (op, f.min(g), f.max(g));
+        if let Some(&r) = self.computed.get(&key) {
+            return r;
+        }
+
+        let (nf, ng) = (self.nodes[f], self.nodes[g]);
+        let var = nf.var.min(ng.var);
+        let (f0, f1) = if nf.var == var { (nf.low, nf.high) } else { (f, f) };
+        let (g0, g1) = if ng.var == var { (ng.low, ng.high) } else { (g, g) };
+        let low = self.apply(op, f0, g0);
+        let high = self.apply(op, f1, g1);
+        let r = self.mk(var, low, high);
+        self.computed.insert(key, r);
+        r
+    }
+
+    fn and(&mut self, f: BddRef, g: BddRef) -> BddRef {
+        self.apply(BddOp::And, f, g)
+    }
+
+    fn or(&mut self, f: BddRef, g: BddRef) -> BddRef {
+        self.apply(BddOp::Or, f, g)
+    }
+
+    fn xor(&mut self, f: BddRef, g: BddRef) -> BddRef {
+        self.apply(BddOp::Xor, f, g)
+    }
+
+    fn not(&mut self, f: BddRef) -> BddRef {
+        self.apply(BddOp::Xor, f, BDD_TRUE)
+    }
+
+    // built bottom up, so that every literal is a single `mk`
+    fn cube(&mut self, c: &Cube) -> BddRef {
+        let mut f = BDD_TRUE;
+        for i in (0..c.len()).rev() {
+            f = match c[i] {
+                TriLogic::True => self.mk(i, BDD_FALSE, f),
+                TriLogic::False => self.mk(i, f, BDD_FALSE),
+                TriLogic::DontCare => f,
+            };
+        }
+        f
+    }
+
+    fn cover(&mut self, F: &CubeList) -> BddRef {
+        let mut f = BDD_FALSE;
+        for c in F.iter() {
+            let g = self.cube(c);
+            f = self.or(f, g);
+        }
+        f
+    }
+
+    // every path to the TRUE terminal is a cube, the paths are pairwise disjoint
+    fn paths(&self, f: BddRef) -> CubeList {
+        fn walk(m: &BddManager, f: BddRef, cube: &mut Cube, paths: &mut CubeList) {
+            if f == BDD_FALSE {
+                return;
+            }
+            if f == BDD_TRUE {
+                paths.push_back(cube.clone());
+                return;
+            }
+            let node = m.nodes[f];
+            cube.set(node.var, TriLogic::False);
+            walk(m, node.low, cube, paths);
+            cube.set(node.var, TriLogic::True);
+            walk(m, node.high, cube, paths);
+            cube.set(node.var, TriLogic::DontCare);
+        }
+        let mut paths = LinkedList::new();
+        // the terminals are labelled with the number of variables
+        let num_vars = self.nodes[BDD_FALSE].var;
+        walk(self, f, &mut Cube::dont_cares(num_vars), &mut paths);
+        paths
+    }
+
+    // the path cover expanded against the off-set and made irredundant
+    fn irredundant_cover(&mut self, f: BddRef) -> CubeList {
+        let off = self.not(f);
+        let R = self.paths(off);
+        irredundant(&expand(&self.paths(f), &R), &LinkedList::new())
+    }
+}
+
+#[test]
+fn bdd_test() {
+    let mut m = BddManager::new(3);
+    let (a, b) = (m.var(0), m.var(1));
+    let ab = m.and(a, b);
+    let ba = m.and(b, a);
+    assert!(ab == ba, "canonical: ab == ba");
+    let na = m.not(a);
+    let a_or_na = m.or(a, na);
+    assert!(a_or_na == BDD_TRUE, "a + a' == 1");
+    let nodes = m.num_nodes();
+    let _ = m.xor(ab, ab);
+    assert!(m.num_nodes() == nodes, "f ^ f needs no new nodes");
+
+    // F = ab + a'c, from cover and back
+    let F: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::False, TriLogic::DontCare, TriLogic::True],
+    ]);
+    let f = m.cover(&F);
+    let G = m.irredundant_cover(f);
+    assert!(cover_implies(&F, &G) && cover_implies(&G, &F), "cover round trip");
+    assert!(m.cover(&G) == f, "same function, same node");
+    let compl = Complement(3, &F);
+    let not_f = m.not(f);
+    assert!(m.cover(&compl) == not_f, "Complement agrees with the BDD");
+}
+
+#[test]
+fn bdd_cross_validate_complement_test() {
+    let num_vars = 8;
+    let mut gen = CoverGenerator::new(12345, num_vars, 12, 0.5);
+    for _ in 0..50 {
+        let F = gen.cover();
+        let mut m = BddManager::new(num_vars);
+        let f = m.cover(&F);
+        let not_f = m.not(f);
+        assert!(m.cover(&Complement(num_vars, &F)) == not_f, "Complement(F) == F'");
+        let G = m.irredundant_cover(f);
+        assert!(m.cover(&G) == f, "BDD to cover round trip");
+    }
+}
+
+// checks the outputs of two PLAs are the same function outside of their dont care sets,
+// returns the first output that differs with a cover of the inputs where it differs
+fn equivalent_outputs(a: &Pla, b: &Pla) -> Result<(), (usize, CubeList)> {
+    let mut m = BddManager::new(a.num_inputs);
+    for j in 0..a.num_outputs {
+        let (fa, fb) = (m.cover(&a.on_sets[j]), m.cover(&b.on_sets[j]));
+        let (da, db) = (m.cover(&a.dc_sets[j]), m.cover(&b.dc_sets[j]));
+        let diff = m.xor(fa, fb);
+        let dc = m.or(da, db);
+        let care = m.not(dc);
+        let differs = m.and(diff, care);
+        if differs != BDD_FALSE {
+            return Err((j, m.irredundant_cover(differs)));
+        }
+    }
+    Ok(())
+}
+
+// random covers for testing: up to `max_cubes` cubes, every variable
+// is a literal of a cube with the probability `density`
+struct CoverGenerator {
+    state: u64,
+    num_vars: usize,
+    max_cubes: usize,
+    density: f64,
+}
+
+impl CoverGenerator {
+    fn new(seed: u64, num_vars: usize, max_cubes: usize, density: f64) -> CoverGenerator {
+        CoverGenerator {
+            // xorshift gets stuck at zero
+            state: seed ^ 0x9E37_79B9_7F4A_7C15,
+            num_vars,
+            max_cubes,
+            density,
+        }
+    }
+
+    // xorshift64*
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn cube(&mut self) -> Cube {
+        let mut cube = Cube::dont_cares(self.num_vars);
+        for i in 0..self.num_vars {
+            if self.next_f64() < self.density {
+                cube.set(i, if self.next_u64() & 1 == 1 {
+                    TriLogic::True
+                } else {
+                    TriLogic::False
+                });
+            }
+        }
+        cube
+    }
+
+    fn cover(&mut self) -> CubeList {
+        let num_cubes = 1 + (self.next_u64() % self.max_cubes as u64) as usize;
+        (0..num_cubes).map(|_| self.cube()).collect()
+    }
+}
+
+// the largest truth table is 2^20 bits, 128KiB
+const MAX_TRUTH_TABLE_VARS: usize = 20;
+
+// the on-set of the cover as a bitset of minterms, variable i is bit i of the minterm
+fn truth_table(num_vars: usize, F: &CubeList) -> Vec<u64> {
+    assert!(num_vars <= MAX_TRUTH_TABLE_VARS, "too many variables for a truth table");
+    let num_minterms = 1usize << num_vars;
+    let mut table = vec![0u64; num_minterms.div_ceil(64)];
+    for c in F.iter() {
+        // the literals fix bits of the minterm, the dont cares run over all the values
+        let mut fixed = 0usize;
+        let mut free = 0usize;
+        for i in 0..num_vars {
+            match c[i] {
+                TriLogic::True => fixed |= 1 << i,
+                TriLogic::False => {}
+                TriLogic::DontCare => free |= 1 << i,
+            }
+        }
+        let mut subset = 0usize;
+        loop {
+            let m = fixed | subset;
+            table[m / 64] |= 1 << (m % 64);
+            // next subset of the free bits
+            subset = subset.wrapping_sub(free) & free;
+            if subset == 0 {
+                break;
+            }
+        }
+    }
+    table
+}
+
+// C is the complement of F iff they are disjoint and cover all the minterms together
+fn check_complement(num_vars: usize, F: &CubeList, C: &CubeList) -> Result<(), String> {
+    let (tf, tc) = (truth_table(num_vars, F), truth_table(num_vars, C));
+    let num_minterms = 1usize << num_vars;
+    for m in 0..num_minterms {
+        let (in_f, in_c) = (tf[m / 64] >> (m % 64) & 1 == 1, tc[m / 64] >> (m % 64) & 1 == 1);
+        if in_f && in_c {
+            return Err(format!("minterm {:0w$b} is in both F and its complement", m, w = num_vars));
+        }
+        if !in_f && !in_c {
+            return Err(format!("minterm {:0w$b} is in neither F nor its complement", m, w = num_vars));
+        }
+    }
+    Ok(())
+}
+
+// checks `Complement` against the truth table on random covers,
+// returns the number of covers checked
+fn selfcheck(
+    seed: u64,
+    iterations: usize,
+    num_vars: usize,
+    max_cubes: usize,
+    density: f64,
+) -> Result<usize, String> {
+    let mut gen = CoverGenerator::new(seed, num_vars, max_cubes, density);
+    for it in 0..iterations {
+        let F = gen.cover();
+        let compl = Complement(num_vars, &F);
+        check_complement(num_vars, &F, &compl)
+            .map_err(|msg| format!("iteration {}: {}\nF: {:?}", it, msg, F))?;
+        if ParallelComplement(num_vars, &F) != compl {
+            return Err(format!("iteration {}: parallel complement differs\nF: {:?}", it, F));
+        }
+    }
+    Ok(iterations)
+}
+
+#[test]
+fn truth_table_test() {
+    // F = ab + a'c over (a, b, c) = bits (0, 1, 2)
+    let F: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::False, TriLogic::DontCare, TriLogic::True],
+    ]);
+    assert!(truth_table(3, &F) == vec![0b1101_1000], "minterms 3, 4, 6, 7");
+    assert!(truth_table(3, &Complement(3, &LinkedList::new())) == vec![0xff]);
+    assert!(check_complement(3, &F, &Complement(3, &F)).is_ok());
+    assert!(check_complement(3, &F, &F).is_err(), "F is not its own complement");
+    assert!(check_complement(3, &F, &LinkedList::new()).is_err(), "0 is not F'");
+}
+
+#[test]
+fn complement_property_test() {
+    // (num_vars, max_cubes, density) from sparse to dense covers
+    let configs = [
+        (4, 4, 0.5),
+        (6, 10, 0.3),
+        (8, 20, 0.6),
+        (10, 40, 0.4),
+        (12, 60, 0.8),
+        (16, 30, 0.25),
+    ];
+    for (k, &(num_vars, max_cubes, density)) in configs.iter().enumerate() {
+        if let Err(msg) = selfcheck(k as u64, 40, num_vars, max_cubes, density) {
+            panic!("{} vars, {} cubes, density {}: {}", num_vars, max_cubes, density, msg);
+        }
+    }
+}
+
+// all the prime implicants of F by iterated consensus: consensus terms not contained
+// in a cube are added, the cubes they contain are dropped, until nothing changes
+fn prime_implicants(F: &CubeList) -> CubeList {
+    let mut primes: Vec<Cube> = single_cube_containment(F).into_iter().collect();
+    let mut i = 0;
+    while i < primes.len() {
+        let mut j = 0;
+        while j < i && i < primes.len() {
+            if let Some(c) = primes[i].consensus(&primes[j]) {
+                if !primes.iter().any(|p| p.contains(&c)) {
+                    // keep the order of the pairs already processed
+                    let (pi, pj) = (primes[i].clone(), primes[j].clone());
+                    primes.retain(|p| !c.contains(p));
+                    primes.push(c);
+                    // restart the pass from the surviving cubes of the pair
+                    i = primes.iter().position(|p| *p == pi || *p == pj).unwrap_or(0);
+                    j = 0;
+                    continue;
+                }
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    LinkedList::from_iter(primes)
+}
+
+// the rows of the covering matrix: for every minterm of the on-set outside
+// of the dont care set the primes covering it, duplicate rows are merged
+fn covering_rows(num_vars: usize, F: &CubeList, D: &CubeList, primes: &[Cube]) -> Vec<Vec<usize>> {
+    let on = truth_table(num_vars, F);
+    let dc = truth_table(num_vars, D);
+    let mut rows: Vec<Vec<usize>> = Vec::new();
+    for m in 0..1usize << num_vars {
+        if on[m / 64] >> (m % 64) & 1 == 0 || dc[m / 64] >> (m % 64) & 1 == 1 {
+            continue;
+        }
+        let row: Vec<usize> = primes.iter()
+            .enumerate()
+            .filter(|&(_, p)| {
+                (0..num_vars).all(|i| match p[i] {
+                    TriLogic::True => m >> i & 1 == 1,
+                    TriLogic::False => m >> i & 1 == 0,
+                    TriLogic::DontCare => true,
+                })
+            })
+            .map(|(k, _)| k)
+            .collect();
+        rows.push(row);
+    }
+    rows.sort();
+    rows.dedup();
+    rows
+}
+
+// the columns of the primes which are the only cover of some minterm
+fn essential_primes(rows: &[Vec<usize>]) -> Vec<usize> {
+    let mut essential: Vec<usize> = rows.iter()
+        .filter(|row| row.len() == 1)
+        .map(|row| row[0])
+        .collect();
+    essential.sort();
+    essential.dedup();
+    essential
+}
+
+// branch and bound solver of the unate covering problem: the cheapest set of
+// columns such that every row has one of them
+struct UnateCovering<'a> {
+    costs: &'a [usize],
+    best_cost: usize,
+    best: Option<Vec<usize>>,
+}
+
+impl<'a> UnateCovering<'a> {
+    fn solve(costs: &'a [usize], rows: Vec<Vec<usize>>) -> Option<Vec<usize>> {
+        let mut solver = UnateCovering {
+            costs,
+            best_cost: usize::MAX,
+            best: None,
+        };
+        solver.branch(rows, &mut Vec::new(), 0);
+        solver.best
+    }
+
+    fn branch(&mut self, mut rows: Vec<Vec<usize>>, chosen: &mut Vec<usize>, mut cost: usize) {
+        let num_chosen = chosen.len();
+        loop {
+            if rows.iter().any(|row| row.is_empty()) {
+                // infeasible
+                chosen.truncate(num_chosen);
+                return;
+            }
+            // essential columns
+            if let Some(c) = rows.iter().find(|row| row.len() == 1).map(|row| row[0]) {
+                chosen.push(c);
+                cost += self.costs[c];
+                rows.retain(|row| !row.contains(&c));
+                continue;
+            }
+            // row dominance: a row containing another row is covered with it
+            let num_rows = rows.len();
+            rows.sort_by_key(|row| row.len());
+            let mut kept: Vec<Vec<usize>> = Vec::with_capacity(rows.len());
+            for row in rows.into_iter() {
+                if !kept.iter().any(|k| k.iter().all(|c| row.contains(c))) {
+                    kept.push(row);
+                }
+            }
+            rows = kept;
+            // column dominance: a column is dropped if another one covers
+            // all of its rows at no higher cost
+            let mut columns: Vec<usize> = rows.iter().flat_map(|row| row.iter().cloned()).collect();
+            columns.sort();
+            columns.dedup();
+            let rows_of = |c: usize| -> Vec<usize> {
+                (0..rows.len()).filter(|&r| rows[r].contains(&c)).collect()
+            };
+            let covered: Vec<Vec<usize>> = columns.iter().map(|&c| rows_of(c)).collect();
+            let dominated = (0..columns.len()).find(|&a| {
+                (0..columns.len()).any(|b| {
+                    b != a && self.costs[columns[b]] <= self.costs[columns[a]] &&
+                        covered[a].iter().all(|r| covered[b].contains(r)) &&
+                        (covered[a].len() < covered[b].len() ||
+                             self.costs[columns[b]] < self.costs[columns[a]] || b < a)
+                })
+            });
+            if let Some(a) = dominated {
+                let c = columns[a];
+                for row in rows.iter_mut() {
+                    row.retain(|&x| x != c);
+                }
+                continue;
+            }
+            if rows.len() == num_rows {
+                break;
+            }
+        }
+
+        if rows.is_empty() {
+            if cost < self.best_cost {
+                self.best_cost = cost;
+                self.best = Some(chosen.clone());
+            }
+            chosen.truncate(num_chosen);
+            return;
+        }
+
+        // lower bound from rows with no common columns
+        let mut independent: Vec<&Vec<usize>> = Vec::new();
+        let mut by_length: Vec<&Vec<usize>> = rows.iter().collect();
+        by_length.sort_by_key(|row| row.len());
+        for row in by_length.into_iter() {
+            if independent.iter().all(|r| r.iter().all(|c| !row.contains(c))) {
+                independent.push(row);
+            }
+        }
+        let lower_bound: usize = independent.iter()
+            .map(|row| row.iter().map(|&c| self.costs[c]).min().unwrap())
+            .sum();
+        if cost + lower_bound >= self.best_cost {
+            chosen.truncate(num_chosen);
+            return;
+        }
+
+        // branch on the column of the shortest row which covers most rows
+        let shortest = rows.iter().min_by_key(|row| row.len()).unwrap();
+        let c = *shortest.iter()
+            .max_by_key(|&&c| rows.iter().filter(|row| row.contains(&c)).count())
+            .unwrap();
+
+        let with_c: Vec<Vec<usize>> = rows.iter().filter(|row| !row.contains(&c)).cloned().collect();
+        chosen.push(c);
+        self.branch(with_c, chosen, cost + self.costs[c]);
+        chosen.pop();
+
+        let without_c: Vec<Vec<usize>> = rows.iter()
+            .map(|row| row.iter().cloned().filter(|&x| x != c).collect())
+            .collect();
+        self.branch(without_c, chosen, cost);
+        chosen.truncate(num_chosen);
+    }
+}
+
+// exact two-level minimization: the cheapest cover by primes, fewest cubes first,
+// then fewest literals
+fn ExactMinimize(num_vars: usize, F: &CubeList, D: &CubeList) -> CubeList {
+    let primes: Vec<Cube> = prime_implicants(&OR(F, D)).into_iter().collect();
+    let rows = covering_rows(num_vars, F, D, &primes);
+    // the essential primes are in every cover, the search is left with the other rows
+    let essential = essential_primes(&rows);
+    let rows: Vec<Vec<usize>> = rows.into_iter()
+        .filter(|row| !row.iter().any(|c| essential.contains(c)))
+        .collect();
+    // a cube outweighs the literals of any cover
+    let cube_weight = 1 + num_vars * primes.len();
+    let costs: Vec<usize> = primes.iter().map(|p| cube_weight + count_all_not_dont_cares(p)).collect();
+    let mut chosen = UnateCovering::solve(&costs, rows).expect("the primes cover the on-set");
+    chosen.extend(essential);
+    chosen.sort();
+    chosen.iter().map(|&k| primes[k].clone()).collect()
+}
+
+#[test]
+fn prime_implicants_test() {
+    // F = ab + a'c, the primes are ab, a'c and the consensus bc
+    let F: CubeList = cube_list(vec![
+        vec![TriLogic::True, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::False, TriLogic::DontCare, TriLogic::True],
+    ]);
+    let no_dc: CubeList = LinkedList::new();
+    let primes: Vec<Cube> = prime_implicants(&F).into_iter().collect();
+    assert!(primes.len() == 3, "ab, a'c, bc");
+    assert!(primes.iter().any(|p| *p == Cube::from(vec![TriLogic::DontCare, TriLogic::True, TriLogic::True])));
+    let essential = essential_primes(&covering_rows(3, &F, &no_dc, &primes));
+    assert!(essential.len() == 2, "bc is not essential");
+    assert!(essential.iter().all(|&k| count_all_not_dont_cares(&primes[k]) == 2 && primes[k][0] != TriLogic::DontCare));
+    let exact = ExactMinimize(3, &F, &LinkedList::new());
+    assert!(cover_cost(&exact) == (2, 4), "ab + a'c");
+
+    // the cyclic core a'b + b'c + c'a + ab' + bc' + ca' has no essential primes
+    // and two minimum covers of 3 cubes
+    let cyclic: CubeList = cube_list(vec![
+        vec![TriLogic::False, TriLogic::True, TriLogic::DontCare],
+        vec![TriLogic::DontCare, TriLogic::False, TriLogic::True],
+        vec![TriLogic::True, TriLogic::DontCare, TriLogic::False],
+        vec![TriLogic::True, TriLogic::False, TriLogic::DontCare],
+        vec![TriLogic::DontCare, TriLogic::True, TriLogic::False],
+        vec![TriLogic::False, TriLogic::DontCare, TriLogic::True],
+    ]);
+    let primes: Vec<Cube> = prime_implicants(&cyclic).into_iter().collect();
+    assert!(primes.len() == 6);
+    assert!(essential_primes(&covering_rows(3, &cyclic, &no_dc, &primes)).is_empty());
+    let exact = ExactMinimize(3, &cyclic, &LinkedList::new());
+    assert!(cover_cost(&exact) == (3, 6), "cyclic core");
+    assert!(truth_table(3, &exact) == truth_table(3, &cyclic));
+}
+
+#[test]
+fn exact_vs_heuristic_test() {
+    for i in 1..6 {
+        let fname = format!("data/UnateRecursiveComplement/part{}.pcn", i);
+        let (num_vars, F) = read_pcn(&fname).unwrap();
+        for G in [F.clone(), Complement(num_vars, &F)].iter() {
+            let exact = ExactMinimize(num_vars, G, &LinkedList::new());
+            let heuristic = Minimize(num_vars, G);
+            assert!(
+                truth_table(num_vars, &exact) == truth_table(num_vars, G),
+                "{}: exact cover is equivalent",
+                fname
+            );
+            assert!(
+                truth_table(num_vars, &heuristic) == truth_table(num_vars, G),
+                "{}: heuristic cover is equivalent",
+                fname
+            );
+            assert!(
+                cover_cost(&exact) <= cover_cost(&heuristic),
+                "{}: exact {:?} is not worse than heuristic {:?}",
+                fname,
+                cover_cost(&exact),
+                cover_cost(&heuristic)
+            );
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CoverFormat {
+    Pcn,
+    Pla,
+    // output only formats
+    Sop,
+    Blif,
+    Verilog,
+}
+
+// espresso `.type`: which of on-set (f), dont care set (d) and off-set (r)
+// are given by the output part of the cubes
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+enum PlaType {
+    F,
+    FD,
+    FR,
+    FDR,
+}
+
+// Berkeley PLA, a cover per output over the same inputs
+#[derive(Clone, Debug)]
+struct Pla {
+    num_inputs: usize,
+    num_outputs: usize,
+    pla_type: PlaType,
+    input_labels: Option<Vec<String>>,
+    output_labels: Option<Vec<String>>,
+    on_sets: Vec<CubeList>,
+    dc_sets: Vec<CubeList>,
+    off_sets: Vec<CubeList>,
+}
+
+impl Pla {
+    fn single_output(num_inputs: usize, F: CubeList) -> Pla {
+        Pla {
+            num_inputs,
+            num_outputs: 1,
+            pla_type: PlaType::FD,
+            input_labels: None,
+            output_labels: None,
+            on_sets: vec![F],
+            dc_sets: vec![LinkedList::new()],
+            off_sets: vec![LinkedList::new()],
+        }
+    }
+}
+
+fn parse_pla<R: BufRead>(reader: R) -> Result<Pla, String> {
+    let mut num_inputs: Option<usize> = None;
+    let mut num_outputs: Option<usize> = None;
+    let mut num_products: Option<usize> = None;
+    let mut pla_type = PlaType::FD;
+    let mut input_labels = None;
+    let mut output_labels = None;
+    let mut on_sets: Vec<CubeList> = Vec::new();
+    let mut dc_sets: Vec<CubeList> = Vec::new();
+    let mut off_sets: Vec<CubeList> = Vec::new();
+    let mut num_cubes = 0;
+
+    for (idx, maybe_line) in reader.lines().enumerate() {
+        let line_num = idx + 1;
+        let line = maybe_line.map_err(|e| format!("line {}: {}", line_num, e))?;
+        // strip comments
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = |msg: &str| format!("line {}: {}", line_num, msg);
+        let parse_count = |word: Option<&str>| -> Result<usize, String> {
+            word.and_then(|w| w.parse().ok())
+                .ok_or_else(|| err("a number expected"))
+        };
+
+        if line.starts_with('.') {
+            let mut words = line.split_whitespace();
+            match words.next().unwrap() {
+                ".i" => num_inputs = Some(parse_count(words.next())?),
+                ".o" => num_outputs = Some(parse_count(words.next())?),
+                ".p" => num_products = Some(parse_count(words.next())?),
+                ".ilb" => input_labels = Some(words.map(String::from).collect()),
+                ".ob" => output_labels = Some(words.map(String::from).collect()),
+                ".type" => {
+                    pla_type = match words.next() {
+                        Some("f") => PlaType::F,
+                        Some("fd") => PlaType::FD,
+                        Some("fr") => PlaType::FR,
+                        Some("fdr") => PlaType::FDR,
+                        _ => return Err(err("unsupported .type")),
+                    }
+                }
+                ".e" | ".end" => break,
+                _ => {} // other keywords are ignored
+            }
+            continue;
+        }
+
+        let (ni, no) = match (num_inputs, num_outputs) {
+            (Some(ni), Some(no)) => (ni, no),
+            (Some(ni), None) => (ni, 1),
+            _ => return Err(err(".i expected before the first cube")),
+        };
+        if on_sets.is_empty() {
+            on_sets = vec![LinkedList::new(); no];
+            dc_sets = vec![LinkedList::new(); no];
+            off_sets = vec![LinkedList::new(); no];
+        }
+        let literals: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+        if literals.len() != ni + no {
+            return Err(err(&format!("expected {} input and {} output literals", ni, no)));
+        }
+        let mut cube = Cube::dont_cares(ni);
+        for (i, ch) in literals[..ni].iter().enumerate() {
+            match *ch {
+                '1' => cube.set(i, TriLogic::True),
+                '0' => cube.set(i, TriLogic::False),
+                '-' | '2' => {}
+                _ => return Err(err(&format!("invalid input literal '{}'", ch))),
+            }
+        }
+        for (j, ch) in literals[ni..].iter().enumerate() {
+            match *ch {
+                '1' | '4' => on_sets[j].push_back(cube.clone()),
+                '-' | '2' if pla_type == PlaType::FD || pla_type == PlaType::FDR => {
+                    dc_sets[j].push_back(cube.clone())
+                }
+                '0' | '3' if pla_type == PlaType::FR || pla_type == PlaType::FDR => {
+                    off_sets[j].push_back(cube.clone())
+                }
+                '0' | '3' | '-' | '2' | '~' => {} // no meaning for this output
+                _ => return Err(err(&format!("invalid output literal '{}'", ch))),
+            }
+        }
+        num_cubes += 1;
+    }
+
+    let num_inputs = num_inputs.ok_or_else(|| String::from(".i is missing"))?;
+    let num_outputs = num_outputs.unwrap_or(1);
+    if let Some(np) = num_products {
+        if np != num_cubes {
+            return Err(format!(".p {} declared but {} cubes found", np, num_cubes));
+        }
+    }
+    if on_sets.is_empty() {
+        on_sets = vec![LinkedList::new(); num_outputs];
+        dc_sets = vec![LinkedList::new(); num_outputs];
+        off_sets = vec![LinkedList::new(); num_outputs];
+    }
+    // as in espresso, with an off-set given the points in neither F nor R are dont cares
+    if pla_type == PlaType::FR || pla_type == PlaType::FDR {
+        for j in 0..num_outputs {
+            let mut unspecified = Complement(num_inputs, &OR(&on_sets[j], &off_sets[j]));
+            dc_sets[j].append(&mut unspecified);
+        }
+    }
+    Ok(Pla {
+        num_inputs,
+        num_outputs,
+        pla_type,
+        input_labels,
+        output_labels,
+        on_sets,
+        dc_sets,
+        off_sets,
+    })
+}
+
+// writes `.type fd`, cubes with the same input part are merged across the outputs
+// the input part of a PLA row, also the rows of a BLIF table
+fn input_part(cube: &Cube) -> String {
+    cube.iter()
+        .map(|t| match t {
+            TriLogic::True => '1',
+            TriLogic::False => '0',
+            TriLogic::DontCare => '-',
+        })
+        .collect()
+}
+
+fn write_pla<W: Write>(w: &mut W, pla: &Pla) -> Result<(), io::Error> {
+    let mut rows: Vec<(Cube, Vec<char>)> = Vec::new();
+    let mut row_of_cube: HashMap<Cube, usize> = HashMap::new();
+    for j in 0..pla.num_outputs {
+        let sets = [(&pla.on_sets[j], '1'), (&pla.dc_sets[j], '-')];
+        for &(set, out_literal) in sets.iter() {
+            for cube in set.iter() {
+                let row = *row_of_cube.entry(cube.clone()).or_insert_with(|| {
+                    rows.push((cube.clone(), vec!['0'; pla.num_outputs]));
+                    rows.len() - 1
+                });
+                rows[row].1[j] = out_literal;
+            }
+        }
+    }
+
+    writeln!(w, ".i {}", pla.num_inputs)?;
+    writeln!(w, ".o {}", pla.num_outputs)?;
+    if let Some(ref labels) = pla.input_labels {
+        writeln!(w, ".ilb {}", labels.join(" "))?;
+    }
+    if let Some(ref labels) = pla.output_labels {
+        writeln!(w, ".ob {}", labels.join(" "))?;
+    }
+    writeln!(w, ".type fd")?;
+    writeln!(w, ".p {}", rows.len())?;
+    for (cube, outputs) in rows.iter() {
+        let outputs: String = outputs.iter().collect();
+        writeln!(w, "{} {}", input_part(cube), outputs)?;
+    }
+    writeln!(w, ".e")?;
+    Ok(())
+}
+
+#[test]
+fn pla_test() {
+    let text = "\
+# a 2-output function
+.i 3
+.o 2
+.ilb a b c
+.ob f g
+.type fd
+.p 4
+1-0 10
+01- 11
+--1 0-
+000 -0
+.e
+";
+    let pla = parse_pla(text.as_bytes()).unwrap();
+    assert!(pla.num_inputs == 3 && pla.num_outputs == 2);
+    assert!(pla.input_labels == Some(vec!["a".into(), "b".into(), "c".into()]));
+    assert!(pla.on_sets[0].len() == 2 && pla.on_sets[1].len() == 1, "on-sets");
+    assert!(pla.dc_sets[0].len() == 1 && pla.dc_sets[1].len() == 1, "dc-sets");
+    assert!(
+        *pla.dc_sets[1].front().unwrap() ==
+            Cube::from(vec![TriLogic::DontCare, TriLogic::DontCare, TriLogic::True]),
+        "g dont cares c"
+    );
+
+    // round trip
+    let mut out: Vec<u8> = Vec::new();
+    write_pla(&mut out, &pla).unwrap();
+    let again = parse_pla(&out[..]).unwrap();
+    assert!(again.output_labels == pla.output_labels);
+    for j in 0..2 {
+        assert!(cover_implies(&again.on_sets[j], &pla.on_sets[j]));
+        assert!(cover_implies(&pla.on_sets[j], &again.on_sets[j]));
+        assert!(cover_implies(&again.dc_sets[j], &pla.dc_sets[j]));
+    }
+
+    assert!(parse_pla(".i 2\n.o 1\n.p 2\n10 1\n".as_bytes()).is_err(), ".p mismatch");
+    assert!(parse_pla(".i 2\n.o 1\n1x 1\n".as_bytes()).is_err(), "bad literal");
+    assert!(parse_pla(".i 2\n.o 1\n101 1\n".as_bytes()).is_err(), "too many literals");
+
+    // f = a c' + a' b with the dont care a' b' c' minimizes to c' + a' b
+    let min = minimize_with_dont_cares(3, &pla.on_sets[0], &pla.dc_sets[0]);
+    assert!(
+        min.len() == 2 && cover_cost(&min).1 == 3,
+        "dont cares are used by minimization"
+    );
+
+    // with `.type fr` the unspecified 01 and 10 are dont cares: f = a or f = b
+    let fr = parse_pla(".i 2\n.o 1\n.type fr\n11 1\n00 0\n.e\n".as_bytes()).unwrap();
+    assert!(fr.dc_sets[0].len() == 2, "fr dont cares");
+    let min = minimize_with_dont_cares(2, &fr.on_sets[0], &fr.dc_sets[0]);
+    assert!(min.len() == 1 && cover_cost(&min).1 == 1, "fr dont cares are used by minimization");
+}
+
+// the input and output names: the labels of the PLA or x1.., y1..
+fn signal_names(labels: &Option<Vec<String>>, prefix: &str, n: usize) -> Vec<String> {
+    match *labels {
+        Some(ref labels) if labels.len() == n => labels.clone(),
+        _ => (1..n + 1).map(|i| format!("{}{}", prefix, i)).collect(),
+    }
+}
+
+// sum of products in the `y1 = x1 x2' + x3` form, 0 and 1 for the constants
+fn write_sop<W: Write>(w: &mut W, pla: &Pla) -> Result<(), io::Error> {
+    let inputs = signal_names(&pla.input_labels, "x", pla.num_inputs);
+    let outputs = signal_names(&pla.output_labels, "y", pla.num_outputs);
+    for (output, on_set) in outputs.iter().zip(pla.on_sets.iter()) {
+        let products: Vec<String> = canonical_order(on_set)
+            .iter()
+            .map(|cube| {
+                if cube.is_all_dont_cares() {
+                    return String::from("1");
+                }
+                let literals: Vec<String> = cube.iter()
+                    .enumerate()
+                    .filter_map(|(i, term)| match term {
+                        TriLogic::True => Some(inputs[i].clone()),
+                        TriLogic::False => Some(format!("{}'", inputs[i])),
+                        TriLogic::DontCare => None,
+                    })
+                    .collect();
+                literals.join(" ")
+            })
+            .collect();
+        let sop = if products.is_empty() {
+            String::from("0")
+        } else {
+            products.join(" + ")
+        };
+        writeln!(w, "{} = {}", output, sop)?;
+    }
+    Ok(())
+}
+
+// BLIF model with a `.names` table per output
+fn write_blif<W: Write>(w: &mut W, model: &str, pla: &Pla) -> Result<(), io::Error> {
+    let inputs = signal_names(&pla.input_labels, "x", pla.num_inputs);
+    let outputs = signal_names(&pla.output_labels, "y", pla.num_outputs);
+    writeln!(w, ".model {}", model)?;
+    writeln!(w, ".inputs {}", inputs.join(" "))?;
+    writeln!(w, ".outputs {}", outputs.join(" "))?;
+    for (output, on_set) in outputs.iter().zip(pla.on_sets.iter()) {
+        // a table without rows is the constant 0
+        writeln!(w, ".names {} {}", inputs.join(" "), output)?;
+        for cube in canonical_order(on_set).iter() {
+            writeln!(w, "{} 1", input_part(cube))?;
+        }
+    }
+    writeln!(w, ".end")?;
+    Ok(())
+}
+
+// names which are not Verilog identifiers are escaped
+fn verilog_identifier(name: &str) -> String {
+    let mut chars = name.chars();
+    let simple = match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        }
+        _ => false,
+    };
+    if simple {
+        String::from(name)
+    } else {
+        format!("\\{} ", name)
+    }
+}
+
+// structural Verilog module with an `assign` per output
+fn write_verilog<W: Write>(w: &mut W, module: &str, pla: &Pla) -> Result<(), io::Error> {
+    let inputs: Vec<String> = signal_names(&pla.input_labels, "x", pla.num_inputs)
+        .iter()
+        .map(|n| verilog_identifier(n))
+        .collect();
+    let outputs: Vec<String> = signal_names(&pla.output_labels, "y", pla.num_outputs)
+        .iter()
+        .map(|n| verilog_identifier(n))
+        .collect();
+    let ports: Vec<String> = inputs.iter()
+        .map(|n| format!("input {}", n))
+        .chain(outputs.iter().map(|n| format!("output {}", n)))
+        .collect();
+    writeln!(w, "module {}({});", verilog_identifier(module), ports.join(", "))?;
+    for (output, on_set) in outputs.iter().zip(pla.on_sets.iter()) {
+        let products: Vec<String> = canonical_order(on_set)
+            .iter()
+            .map(|cube| {
+                if cube.is_all_dont_cares() {
+                    return String::from("1'b1");
+                }
+                let literals: Vec<String> = cube.iter()
+                    .enumerate()
+                    .filter_map(|(i, term)| match term {
+                        TriLogic::True => Some(inputs[i].clone()),
+                        TriLogic::False => Some(format!("~{}", inputs[i])),
+                        TriLogic::DontCare => None,
+                    })
+                    .collect();
+                if literals.len() == 1 {
+                    literals[0].clone()
+                } else {
+                    format!("({})", literals.join(" & "))
+                }
+            })
+            .collect();
+        let expr = if products.is_empty() {
+            String::from("1'b0")
+        } else {
+            products.join(" | ")
+        };
+        writeln!(w, "  assign {} = {};", output, expr)?;
+    }
+    writeln!(w, "endmodule")?;
+    Ok(())
+}
+
+#[test]
+fn emitters_test() {
+    let mut pla = parse_pla(".i 3\n.o 2\n.ilb a b c\n.ob f 2g\n11- 10\n0-1 10\n".as_bytes()).unwrap();
+    pla.on_sets[1] = Complement(3, &LinkedList::new());
+
+    let mut out: Vec<u8> = Vec::new();
+    write_sop(&mut out, &pla).unwrap();
+    assert!(String::from_utf8(out).unwrap() == "f = a' c + a b\n2g = 1\n");
+
+    let mut out: Vec<u8> = Vec::new();
+    write_blif(&mut out, "top", &pla).unwrap();
+    assert!(
+        String::from_utf8(out).unwrap() ==
+            ".model top\n.inputs a b c\n.outputs f 2g\n\
+             .names a b c f\n0-1 1\n11- 1\n.names a b c 2g\n--- 1\n.end\n"
+    );
+
+    let mut out: Vec<u8> = Vec::new();
+    write_verilog(&mut out, "top", &pla).unwrap();
+    assert!(
+        String::from_utf8(out).unwrap() ==
+            "module top(input a, input b, input c, output f, output \\2g );\n\
+             \x20 assign f = (~a & c) | (a & b);\n\
+             \x20 assign \\2g  = 1'b1;\n\
+             endmodule\n"
+    );
+
+    let empty = Pla::single_output(2, LinkedList::new());
+    let mut out: Vec<u8> = Vec::new();
+    write_sop(&mut out, &empty).unwrap();
+    write_verilog(&mut out, "zero", &empty).unwrap();
+    assert!(
+        String::from_utf8(out).unwrap() ==
+            "y1 = 0\nmodule zero(input x1, input x2, output y1);\n  assign y1 = 1'b0;\nendmodule\n"
+    );
+}
+
+// PLA files start with a `.keyword` line, PCN ones with the number of variables
+fn detect_format(fname: &str) -> io::Result<CoverFormat> {
+    let is = File::open(fname)?;
+    for line in BufReader::new(is).lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        return Ok(if line.starts_with('.') {
+            CoverFormat::Pla
+        } else {
+            CoverFormat::Pcn
+        });
+    }
+    Ok(CoverFormat::Pcn)
+}
+
+fn read_cover(fname: &str) -> Result<(CoverFormat, Pla), String> {
+    let format = detect_format(fname).map_err(|e| e.to_string())?;
+    let pla = match format {
+        CoverFormat::Pcn => {
+            let (num_vars, cube_list) = read_pcn(fname).map_err(|e| e.to_string())?;
+            Pla::single_output(num_vars, cube_list)
+        }
+        CoverFormat::Pla => {
+            let is = File::open(fname).map_err(|e| e.to_string())?;
+            parse_pla(BufReader::new(is))?
+        }
+        _ => unreachable!("only PCN and PLA are detected"),
+    };
+    Ok((format, pla))
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    // `--pla`/`--pcn` choose the output format, the input one by default
+    let mut out_format: Option<CoverFormat> = None;
+    let mut parallel = false;
+    let mut exact = false;
+    args.retain(|arg| match arg.as_str() {
+        "--parallel" => {
+            parallel = true;
+            false
+        }
+        "--exact" => {
+            exact = true;
+            false
+        }
+        "--pla" => {
+            out_format = Some(CoverFormat::Pla);
+            false
+        }
+        "--pcn" => {
+            out_format = Some(CoverFormat::Pcn);
+            false
+        }
+        "--sop" => {
+            out_format = Some(CoverFormat::Sop);
+            false
+        }
+        "--blif" => {
+            out_format = Some(CoverFormat::Blif);
+            false
+        }
+        "--verilog" => {
+            out_format = Some(CoverFormat::Verilog);
+            false
+        }
+        _ => true,
+    });
+    let command = match args.get(1).map(|s| s.as_str()) {
+        Some("minimize") | Some("equiv") | Some("selfcheck") => args.remove(1),
+        _ => String::from("complement"),
+    };
+    if exact && command != "minimize" {
+        eprintln!("--exact is an option of minimize");
+        std::process::exit(1);
+    }
+    if command == "selfcheck" {
+        // selfcheck [num_vars [max_cubes [density [iterations [seed]]]]]
+        fn arg<T: std::str::FromStr>(args: &[String], k: usize, what: &str, default: T) -> T {
+            match args.get(k) {
+                None => default,
+                Some(a) => match a.parse() {
+                    Ok(x) => x,
+                    Err(_) => {
+                        eprintln!("selfcheck: `{}` is not a valid {}", a, what);
+                        std::process::exit(1);
+                    }
+                },
+            }
+        }
+        let num_vars: usize = arg(&args, 1, "number of variables", 12);
+        let max_cubes: usize = arg(&args, 2, "number of cubes", 40);
+        let density: f64 = arg(&args, 3, "density", 0.5);
+        let iterations: usize = arg(&args, 4, "number of iterations", 100);
+        let seed: u64 = arg(&args, 5, "seed", 1);
+        if !(1..=MAX_TRUTH_TABLE_VARS).contains(&num_vars) {
+            eprintln!("selfcheck: 1 to {} variables for the truth table check", MAX_TRUTH_TABLE_VARS);
+            std::process::exit(1);
+        }
+        if max_cubes < 1 {
+            eprintln!("selfcheck: covers of at least one cube");
+            std::process::exit(1);
+        }
+        if !(0.0..=1.0).contains(&density) {
+            eprintln!("selfcheck: the density {} is not a probability", density);
+            std::process::exit(1);
+        }
+        if iterations < 1 {
+            eprintln!("selfcheck: at least one iteration");
+            std::process::exit(1);
+        }
+        match selfcheck(seed, iterations, num_vars, max_cubes, density) {
+            Ok(n) => println!("selfcheck passed: {} random covers of {} variables", n, num_vars),
+            Err(msg) => {
+                println!("selfcheck failed: {}", msg);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if args.len() < 3 {
+        println!("plz. gimme input and output fnames");
+        println!("usage: binary_unate [minimize [--exact]] [--pla|--pcn|--sop|--blif|--verilog] [--parallel] <input> <output>");
+        println!("       binary_unate equiv <first.pcn|first.pla> <second.pcn|second.pla>");
+        println!("       binary_unate selfcheck [num_vars [max_cubes [density [iterations [seed]]]]]");
+        return;
+    }
+
+    let inp_fname = args[1].clone();
+    let out_fname = args[2].clone();
+    let (inp_format, pla) = match read_cover(&inp_fname) {
+        Ok(cover) => cover,
+        Err(msg) => {
+            eprintln!("{}: {}", inp_fname, msg);
+            std::process::exit(1);
+        }
+    };
+
+    if command == "equiv" {
+        let other = match read_cover(&out_fname) {
+            Ok((_, other)) => other,
+            Err(msg) => {
+                eprintln!("{}: {}", out_fname, msg);
+                std::process::exit(1);
+            }
+        };
+        if (other.num_inputs, other.num_outputs) != (pla.num_inputs, pla.num_outputs) {
+            println!("not equivalent: different number of inputs or outputs");
+            std::process::exit(1);
+        }
+        match equivalent_outputs(&pla, &other) {
+            Ok(()) => println!("equivalent"),
+            Err((j, counterexample)) => {
+                // containment outside of the dont cares of both
+                let dc = OR(&pla.dc_sets[j], &other.dc_sets[j]);
+                let implies = |a: &Pla, b: &Pla| cover_implies(&a.on_sets[j], &OR(&b.on_sets[j], &dc));
+                if implies(&pla, &other) {
+                    println!("not equivalent: output {} of {} implies the one of {}", j, inp_fname, out_fname);
+                } else if implies(&other, &pla) {
+                    println!("not equivalent: output {} of {} implies the one of {}", j, out_fname, inp_fname);
+                } else {
+                    println!("not equivalent: output {} differs", j);
+                }
+                println!("inputs where output {} differs:", j);
+                for cube in canonical_order(&counterexample).iter() {
+                    println!("{}", input_part(cube));
+                }
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    let minimize = command == "minimize";
+    if exact && pla.num_inputs > MAX_TRUTH_TABLE_VARS {
+        eprintln!("--exact is limited to {} inputs", MAX_TRUTH_TABLE_VARS);
+        std::process::exit(1);
+    }
+
+    let mut result = pla.clone();
+    result.pla_type = PlaType::FD;
+    let num_vars = pla.num_inputs;
+    let num_outputs = pla.num_outputs;
+    if minimize {
+        for j in 0..num_outputs {
+            let min = if exact {
+                ExactMinimize(num_vars, &pla.on_sets[j], &pla.dc_sets[j])
+            } else {
+                minimize_with_dont_cares(num_vars, &pla.on_sets[j], &pla.dc_sets[j])
+            };
+            println!("minimized: {:?}", min);
+            result.on_sets[j] = min;
+        }
+    } else {
+        // the off-sets of the incompletely specified functions, complemented in one pass
+        let care: Vec<CubeList> = (0..num_outputs)
+            .map(|j| OR(&pla.on_sets[j], &pla.dc_sets[j]))
+            .collect();
+        result.on_sets = if parallel {
+            // every output complemented on its own
+            care.par_iter().map(|c| ParallelComplement(num_vars, c)).collect()
+        } else {
+            let compl = MultiOutputComplement(num_vars, num_outputs, &multi_output_cover(num_outputs, &care));
+            split_outputs(num_outputs, &compl)
+        };
+        println!("compl: {:?}", result.on_sets);
+    }
+    for j in 0..num_outputs {
+        result.off_sets[j] = LinkedList::new();
+
+        // print it in binary form
+        for cube in result.on_sets[j].iter() {
+            print!("[ ");
+            for term in cube.iter() {
+                print!("{:02b} ", term as usize);
+            }
+            println!("]");
+        }
+    }
+
+    let out_format = out_format.unwrap_or(inp_format);
+    if out_format == CoverFormat::Pcn && result.num_outputs != 1 {
+        eprintln!("PCN holds a single output, use --pla for {} outputs", result.num_outputs);
+        std::process::exit(1);
+    }
+    // the module name for BLIF and Verilog
+    let model = Path::new(&out_fname)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("top")
+        .replace('.', "_");
+    let mut f = match File::create(&out_fname) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}: {}", out_fname, e);
+            std::process::exit(1);
+        }
+    };
+    let written = match out_format {
+        CoverFormat::Pcn => write_pcn(&mut f, num_vars, &result.on_sets[0]),
+        CoverFormat::Pla => write_pla(&mut f, &result),
+        CoverFormat::Sop => write_sop(&mut f, &result),
+        CoverFormat::Blif => write_blif(&mut f, &model, &result),
+        CoverFormat::Verilog => write_verilog(&mut f, &model, &result),
+    };
+    if let Err(e) = written {
+        eprintln!("{}: {}", out_fname, e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(feature = "use-nightly")]
+fn bench_complement(b: &mut test::Bencher, fname: &str) {
+    let (num_vars, cube_list) = read_pcn(fname).unwrap();
+    b.iter(|| Complement(num_vars, &cube_list))
+}
+
+#[cfg(feature = "use-nightly")]
+#[bench]
+fn complement_part1(b: &mut test::Bencher) {
+    bench_complement(b, "data/UnateRecursiveComplement/part1.pcn");
+}
+
+#[cfg(feature = "use-nightly")]
+#[bench]
+fn complement_part2(b: &mut test::Bencher) {
+    bench_complement(b, "data/UnateRecursiveComplement/part2.pcn");
+}
+
+#[cfg(feature = "use-nightly")]
+#[bench]
+fn complement_part3(b: &mut test::Bencher) {
+    bench_complement(b, "data/UnateRecursiveComplement/part3.pcn");
+}
+
+#[cfg(feature = "use-nightly")]
+#[bench]
+fn complement_part4(b: &mut test::Bencher) {
+    bench_complement(b, "data/UnateRecursiveComplement/part4.pcn");
+}
+
+#[cfg(feature = "use-nightly")]
+#[bench]
+fn complement_part5(b: &mut test::Bencher) {
+    bench_complement(b, "data/UnateRecursiveComplement/part5.pcn");
 }
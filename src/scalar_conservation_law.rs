@@ -51,6 +51,14 @@
 
 extern crate argparse;
 extern crate num;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_yaml;
+extern crate toml;
+extern crate unindent;
+extern crate ndarray;
+extern crate rayon;
 
 use num::{Num, Zero, One, Signed};
 use std::iter::FromIterator;
@@ -59,13 +67,212 @@ use std::cmp::Ordering;
 use std::time::Duration;
 use std::cmp;
 use std::process::exit;
-use argparse::{ArgumentParser, StoreTrue, Store};
+use std::str::FromStr;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write, BufWriter};
+use std::path::Path;
+use std::fmt;
+use unindent::unindent;
+use argparse::{ArgumentParser, StoreTrue, Store, StoreOption};
+use ndarray::{Array2, Axis};
 
 struct Options {
     verbose: bool,
     num_steps: usize,
     num_cells: usize,
     num_runs: usize,
+    scheme: Scheme,
+    limiter: Limiter,
+    integrator: TimeIntegrator,
+    model: Model,
+    numerical_flux: NumericalFlux,
+    riemann_problem: RiemannProblem,
+    config: String,
+    cfl: Option<f64>,
+    convergence: bool,
+    levels: usize,
+    output: String,
+    snapshots: String,
+    mesh: MeshKind,
+    stretch: f64,
+    perturbation: f64,
+    seed: u64,
+    mesh_file: String,
+    problem_2d: Option<RotationTest>,
+    benchmark: bool,
+    threads: usize,
+}
+
+// the command line defaults
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            verbose: false,
+            num_steps: 2000,
+            num_cells: 2000,
+            num_runs: 10,
+            scheme: Scheme::Upwind,
+            limiter: Limiter::VanLeer,
+            integrator: TimeIntegrator::Euler,
+            model: Model::Linear,
+            numerical_flux: NumericalFlux::Godunov,
+            riemann_problem: RiemannProblem::Shock,
+            config: String::new(),
+            cfl: None,
+            convergence: false,
+            levels: 5,
+            output: String::new(),
+            snapshots: String::new(),
+            mesh: MeshKind::Uniform,
+            stretch: 1.0,
+            perturbation: 0.25,
+            seed: 1,
+            mesh_file: String::new(),
+            problem_2d: None,
+            benchmark: false,
+            threads: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MeshKind {
+    Uniform,
+    Stretched,
+    Perturbed,
+}
+
+impl FromStr for MeshKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MeshKind, String> {
+        match s {
+            "uniform" => Ok(MeshKind::Uniform),
+            "stretched" => Ok(MeshKind::Stretched),
+            "perturbed" => Ok(MeshKind::Perturbed),
+            _ => Err(format!("unknown mesh `{}`, expected uniform|stretched|perturbed", s)),
+        }
+    }
+}
+
+// default problem-specific parameters:
+const JUMP: f64 = 0.0f64;
+const X_LEFT: f64 = -0.2f64;
+const X_RIGHT: f64 = 1.0f64;
+const VELOCITY: f64 = 1.0f64;
+
+const TMAX: f64 = 0.8f64;
+// upwind is TVD up to 1, MUSCL with limiters up to 0.5
+const CFL: f64 = 0.9f64;
+
+// spatial discretization: first-order upwind or second-order MUSCL
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scheme {
+    Upwind,
+    Muscl,
+}
+
+impl FromStr for Scheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Scheme, String> {
+        match s {
+            "upwind" => Ok(Scheme::Upwind),
+            "muscl" => Ok(Scheme::Muscl),
+            _ => Err(format!("unknown scheme `{}`, expected upwind|muscl", s)),
+        }
+    }
+}
+
+// TVD slope limiters for the MUSCL reconstruction
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Limiter {
+    Minmod,
+    Superbee,
+    VanLeer,
+    MC,
+}
+
+impl FromStr for Limiter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Limiter, String> {
+        match s {
+            "minmod" => Ok(Limiter::Minmod),
+            "superbee" => Ok(Limiter::Superbee),
+            "vanleer" => Ok(Limiter::VanLeer),
+            "mc" => Ok(Limiter::MC),
+            _ => Err(format!(
+                "unknown limiter `{}`, expected minmod|superbee|vanleer|mc",
+                s
+            )),
+        }
+    }
+}
+
+impl Limiter {
+    // limited slope (undivided) from the left and right differences,
+    // zero at extrema
+    fn slope(&self, dl: f64, dr: f64) -> f64 {
+        if dl * dr <= 0.0f64 {
+            return 0.0f64;
+        }
+        let (a, b) = (dl.abs(), dr.abs());
+        let s = match *self {
+            Limiter::Minmod => a.min(b),
+            Limiter::Superbee => (2.0f64 * a).min(b).max(a.min(2.0f64 * b)),
+            Limiter::VanLeer => 2.0f64 * a * b / (a + b),
+            Limiter::MC => (0.5f64 * (a + b)).min(2.0f64 * a).min(2.0f64 * b),
+        };
+        s.copysign(dl)
+    }
+}
+
+#[test]
+fn limiter_test() {
+    let limiters = [Limiter::Minmod, Limiter::Superbee, Limiter::VanLeer, Limiter::MC];
+    let expected = [1.0f64, 2.0f64, 4.0f64 / 3.0f64, 1.5f64];
+    for (l, e) in limiters.iter().zip(expected.iter()) {
+        assert!((l.slope(1.0, 2.0) - e).abs() < 1e-14, "{:?}", l);
+        assert!((l.slope(-1.0, -2.0) + e).abs() < 1e-14, "{:?} is odd", l);
+        assert!((l.slope(1.0, 2.0) - l.slope(2.0, 1.0)).abs() < 1e-14, "{:?} is symmetric", l);
+        assert!(l.slope(1.0, -2.0) == 0.0, "{:?} vanishes at extrema", l);
+        assert!((l.slope(1.0, 1.0) - 1.0).abs() < 1e-14, "{:?} is exact for linear data", l);
+    }
+}
+
+// strong stability preserving Runge-Kutta schemes
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TimeIntegrator {
+    Euler,
+    SspRk2,
+    SspRk3,
+}
+
+impl FromStr for TimeIntegrator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TimeIntegrator, String> {
+        match s {
+            "euler" => Ok(TimeIntegrator::Euler),
+            "rk2" => Ok(TimeIntegrator::SspRk2),
+            "rk3" => Ok(TimeIntegrator::SspRk3),
+            _ => Err(format!("unknown time integrator `{}`, expected euler|rk2|rk3", s)),
+        }
+    }
+}
+
+impl TimeIntegrator {
+    // Shu-Osher form: stage k computes u = a_k * u^n + (1 - a_k) * (u + dt * L(u))
+    fn stages(&self) -> &'static [f64] {
+        match *self {
+            TimeIntegrator::Euler => &[0.0f64],
+            TimeIntegrator::SspRk2 => &[0.0f64, 0.5f64],
+            TimeIntegrator::SspRk3 => &[0.0f64, 0.75f64, 1.0f64 / 3.0f64],
+        }
+    }
 }
 
 fn min_dx(x: &[f64]) -> f64 {
@@ -133,6 +340,157 @@ fn test_min_dx() {
     assert!(abs_delta < 1e-10, "min_dx");
 }
 
+// cell-centred 1D meshes given by their increasing nodes x[0..ncells + 1],
+// cell `ic` is [x[ic], x[ic + 1]]
+mod mesh {
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+    use std::iter::FromIterator;
+    use super::NGHOST;
+
+    pub fn uniform(x_left: f64, x_right: f64, ncells: usize) -> Vec<f64> {
+        let dx = (x_right - x_left) / ncells as f64;
+        let mut x = Vec::from_iter((0..ncells + 1).map(|ie| x_left + ie as f64 * dx));
+        x[ncells] = x_right;
+        x
+    }
+
+    // geometric stretching: the widths grow by a constant factor from left to
+    // right, `ratio` is the width of the last cell over the width of the first one
+    pub fn stretched(x_left: f64, x_right: f64, ncells: usize, ratio: f64) -> Vec<f64> {
+        if ncells < 2 || (ratio - 1.0f64).abs() < 1e-14 {
+            return uniform(x_left, x_right, ncells);
+        }
+        let q = ratio.powf(1.0f64 / (ncells - 1) as f64);
+        // sum of the widths dx0 * q^ic
+        let dx0 = (x_right - x_left) * (q - 1.0f64) / (q.powi(ncells as i32) - 1.0f64);
+        let mut x = Vec::with_capacity(ncells + 1);
+        let mut xe = x_left;
+        let mut dx = dx0;
+        for _ in 0..ncells {
+            x.push(xe);
+            xe += dx;
+            dx *= q;
+        }
+        x.push(x_right);
+        x
+    }
+
+    // uniform mesh with the interior nodes moved randomly by up to `amplitude` < 1/2
+    // times the uniform width, reproducible for a given seed
+    pub fn perturbed(x_left: f64, x_right: f64, ncells: usize, amplitude: f64, seed: u64) -> Vec<f64> {
+        let mut x = uniform(x_left, x_right, ncells);
+        let dx = (x_right - x_left) / ncells as f64;
+        // xorshift64*
+        let mut state = seed.max(1);
+        for ie in 1..ncells {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            let r = (state.wrapping_mul(0x2545F4914F6CDD1D) >> 11) as f64 / (1u64 << 53) as f64;
+            x[ie] += amplitude * dx * (2.0f64 * r - 1.0f64);
+        }
+        x
+    }
+
+    // every cell split in two
+    pub fn refined(x: &[f64]) -> Vec<f64> {
+        let mut fine = Vec::with_capacity(2 * x.len() - 1);
+        for w in x.windows(2) {
+            fine.push(w[0]);
+            fine.push(0.5f64 * (w[0] + w[1]));
+        }
+        fine.push(x[x.len() - 1]);
+        fine
+    }
+
+    // one node per line, `#` starts a comment
+    pub fn parse_nodes<R: BufRead>(reader: R) -> Result<Vec<f64>, String> {
+        let mut x = Vec::new();
+        for (k, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            let text = line.split('#').next().unwrap_or("").trim();
+            if text.is_empty() {
+                continue;
+            }
+            let xe: f64 = text.parse().map_err(|_| {
+                format!("line {}: invalid node `{}`", k + 1, text)
+            })?;
+            if let Some(&prev) = x.last() {
+                if !(xe > prev) {
+                    return Err(format!("line {}: node {} is not greater than {}", k + 1, xe, prev));
+                }
+            }
+            x.push(xe);
+        }
+        if x.len() < NGHOST + 1 {
+            return Err(format!("at least {} nodes needed, found {}", NGHOST + 1, x.len()));
+        }
+        Ok(x)
+    }
+
+    pub fn read_nodes(fname: &str) -> Result<Vec<f64>, String> {
+        let f = File::open(fname).map_err(|e| e.to_string())?;
+        parse_nodes(BufReader::new(f))
+    }
+
+    // the cell widths with NGHOST ghost cells on each side, copies of the cells
+    // at the other end for periodic meshes and mirror images otherwise
+    pub fn padded_widths(x: &[f64], periodic: bool) -> Vec<f64> {
+        let ncells = x.len() - 1;
+        let width = |ic: usize| x[ic + 1] - x[ic];
+        let mut dx = vec![0.0f64; ncells + 2 * NGHOST];
+        for ic in 0..ncells {
+            dx[ic + NGHOST] = width(ic);
+        }
+        for g in 0..NGHOST {
+            let (left, right) = if periodic {
+                (width(ncells - 1 - g), width(g))
+            } else {
+                (width(g), width(ncells - 1 - g))
+            };
+            dx[NGHOST - 1 - g] = left;
+            dx[ncells + NGHOST + g] = right;
+        }
+        dx
+    }
+
+    #[test]
+    fn mesh_test() {
+        let x = uniform(-0.2, 1.0, 6);
+        assert!(x.len() == 7 && x[0] == -0.2 && x[6] == 1.0);
+
+        let x = stretched(0.0, 1.0, 10, 4.0);
+        let dx = Vec::from_iter(x.windows(2).map(|w| w[1] - w[0]));
+        assert!(x[0] == 0.0 && x[10] == 1.0);
+        assert!((dx[9] / dx[0] - 4.0).abs() < 1e-12, "ratio {}", dx[9] / dx[0]);
+        assert!(dx.windows(2).all(|w| w[1] > w[0]));
+
+        let x = perturbed(0.0, 1.0, 100, 0.4, 7);
+        assert!(x == perturbed(0.0, 1.0, 100, 0.4, 7), "reproducible");
+        assert!(x != perturbed(0.0, 1.0, 100, 0.4, 8));
+        assert!(x[0] == 0.0 && x[100] == 1.0);
+        assert!(x.windows(2).all(|w| w[1] - w[0] > 0.2 * 0.01 - 1e-15));
+
+        let x = refined(&[0.0, 1.0, 3.0]);
+        assert!(x == vec![0.0, 0.5, 1.0, 2.0, 3.0]);
+
+        let dx = padded_widths(&[0.0, 1.0, 3.0, 6.0], false);
+        assert!(dx == vec![2.0, 1.0, 1.0, 2.0, 3.0, 3.0, 2.0]);
+        let dx = padded_widths(&[0.0, 1.0, 3.0, 6.0], true);
+        assert!(dx == vec![2.0, 3.0, 1.0, 2.0, 3.0, 1.0, 2.0]);
+
+        let nodes = "# nodes\n0.0\n0.1  # refined\n\n0.3\n1.0\n";
+        assert!(parse_nodes(nodes.as_bytes()).unwrap() == vec![0.0, 0.1, 0.3, 1.0]);
+        assert!(parse_nodes("0.0\n0.5\n0.5\n1.0\n".as_bytes()).is_err(), "not increasing");
+        assert!(parse_nodes("0.0\nhalf\n1.0\n".as_bytes()).is_err(), "invalid node");
+        assert!(parse_nodes("0.0\n1.0\n".as_bytes()).is_err(), "too few nodes");
+    }
+}
+
+// number of ghost cells on each side: cell `ic` is stored in `u[ic + NGHOST]`
+const NGHOST: usize = 2;
+
 fn do_computation(
     nsteps: usize,
     ncells: usize,
@@ -148,17 +506,19 @@ fn do_computation(
     x: &Vec<f64>,
     u: &mut Vec<f64>,
     fp_way: bool,
-) {
+) -> f64 {
     let mut istep = 0;
     let mut t = 0.0f64;
     let mut flux = vec![0.0f64; x.len()];
 
     // loop over timesteps
     while istep < nsteps && t < tmax {
+        // the last step ends at tmax
+        let dt = dt.min(tmax - t);
 
         // right boundary condition: outgoing wave
         for ic in ncells..lc {
-            u[ic + 2] = u[ncells + 1];
+            u[ic + NGHOST] = u[ncells + NGHOST - 1];
         }
         // left boundary condition: specified value
         for ic in 0..fc {
@@ -169,20 +529,20 @@ fn do_computation(
         // assumes velocity > 0
         let vdt = velocity * dt;
         for ie in ifirst..ilast + 1 {
-            flux[ie] = vdt * u[ie + 1];
+            flux[ie] = vdt * u[ie + NGHOST - 1];
         }
 
-        // conservative difference
+        // conservative difference with the width of each cell
         if !fp_way {
             for ic in ifirst..ilast {
-                u[ic + 2] -= (flux[ic + 1] - flux[ic]) / (x[ic + 1] - x[ic])
+                u[ic + NGHOST] -= (flux[ic + 1] - flux[ic]) / (x[ic + 1] - x[ic])
             }
         } else {
             let dfdx = Vec::from_iter(flux.windows(2).zip(x.windows(2)).map(|(f, x)| {
                 (f[1] - f[0]) / (x[1] - x[0])
             }));
             for ic in ifirst..ilast {
-                u[ic + 2] -= dfdx[ic];
+                u[ic + NGHOST] -= dfdx[ic];
             }
         }
 
@@ -190,149 +550,2904 @@ fn do_computation(
         t += dt;
         istep += 1
     }
+    t
 }
 
-fn main() {
-    let mut options = Options {
-        verbose: false,
-        num_steps: 2000,
-        num_cells: 2000,
-        num_runs: 10,
-    };
+// variants of the upwind update of do_computation for linear advection with
+// velocity > 0; every kernel maps `u` = [upwind halo cell, cells...] to the new
+// cell values with the same floating point operations, so the results agree bitwise
+mod kernels {
+    use std::iter::FromIterator;
+    use std::mem;
+    use rayon::prelude::*;
+    use super::NGHOST;
 
-    {
-        let mut ap = ArgumentParser::new();
-        ap.set_description("Time elapsing of model PDE for scalar conservation law.");
-        ap.refer(&mut options.verbose).add_option(
-            &["-v", "--verbose"],
-            StoreTrue,
-            "set verbose",
-        );
-        ap.refer(&mut options.num_steps).add_option(
-            &["-t", "--num_steps"],
-            Store,
-            "set number of time steps",
-        );
-        ap.refer(&mut options.num_cells).add_option(
-            &["-n", "--num_cells"],
-            Store,
-            "set number of grid cells",
-        );
-        ap.refer(&mut options.num_runs).add_option(
-            &["-b", "--num_runs"],
-            Store,
-            "set number of runs",
-        );
-        match ap.parse_args() {
-            Ok(()) => {}
-            Err(x) => {
-                exit(x);
-            }
-        }
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Kernel {
+        // indexed loop
+        Loop,
+        // iterator over `windows(2)`
+        Windows,
+        // chunks of explicit SIMD lanes, AVX when available
+        Simd,
+        // rayon over subdomains exchanging halo cells every step
+        Rayon,
     }
 
-    if options.verbose {
-        println!(
-            "number of time steps : {} number of grid cells : {} number of runs : {}",
-            options.num_steps,
-            options.num_cells,
-            options.num_runs
-        );
-    }
+    pub const KERNELS: [Kernel; 4] = [Kernel::Loop, Kernel::Windows, Kernel::Simd, Kernel::Rayon];
 
-    // problem-specific parameters:
-    const JUMP: f64 = 0.0f64;
-    const X_LEFT: f64 = -0.2f64;
-    const X_RIGHT: f64 = 1.0f64;
-    const STATELFT: f64 = 2.0f64;
-    const STATERGT: f64 = 0.0f64;
-    const VELOCITY: f64 = 1.0f64;
+    pub fn update_loop(vdt: f64, dx: &[f64], u: &[f64], unew: &mut [f64]) {
+        for ic in 0..unew.len() {
+            unew[ic] = u[ic + 1] - (vdt * u[ic + 1] - vdt * u[ic]) / dx[ic];
+        }
+    }
 
-    const TMAX: f64 = 0.8f64;
-    const CFL: f64 = 0.9f64;
+    pub fn update_windows(vdt: f64, dx: &[f64], u: &[f64], unew: &mut [f64]) {
+        for ((v, w), &h) in unew.iter_mut().zip(u.windows(2)).zip(dx) {
+            *v = w[1] - (vdt * w[1] - vdt * w[0]) / h;
+        }
+    }
 
-    // array bounds:
-    let nsteps = options.num_steps;
-    let ncells = options.num_cells;
-    const FC: usize = 2;
-    let lc = ncells + 2;
-    const IFIRST: usize = 1;
-    let ilast = ncells - 1;
+    const LANES: usize = 4;
 
-    // work arrays
-    // #   double precision
-    // #  &  u(-2:ncells+1),
-    // #  &  x(0:ncells),
-    // #  &  flux(0:ncells)
+    // portable form: fixed size lanes the compiler maps onto vector registers
+    pub fn update_lanes(vdt: f64, dx: &[f64], u: &[f64], unew: &mut [f64]) {
+        let n = unew.len() - unew.len() % LANES;
+        for (k, v) in unew[..n].chunks_exact_mut(LANES).enumerate() {
+            let i = k * LANES;
+            let mut ul = [0.0f64; LANES];
+            let mut uc = [0.0f64; LANES];
+            let mut h = [0.0f64; LANES];
+            ul.copy_from_slice(&u[i..i + LANES]);
+            uc.copy_from_slice(&u[i + 1..i + 1 + LANES]);
+            h.copy_from_slice(&dx[i..i + LANES]);
+            for l in 0..LANES {
+                v[l] = uc[l] - (vdt * uc[l] - vdt * ul[l]) / h[l];
+            }
+        }
+        update_loop(vdt, &dx[n..], &u[n..], &mut unew[n..]);
+    }
 
-    let mut u = vec![0.0f64; ncells + 4];
-    let mut x = vec![0.0f64; ncells + 1];
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx")]
+    unsafe fn update_avx(vdt: f64, dx: &[f64], u: &[f64], unew: &mut [f64]) {
+        use std::arch::x86_64::*;
+        let n = unew.len() - unew.len() % LANES;
+        let v = _mm256_set1_pd(vdt);
+        for i in (0..n).step_by(LANES) {
+            let ul = _mm256_loadu_pd(u.as_ptr().add(i));
+            let uc = _mm256_loadu_pd(u.as_ptr().add(i + 1));
+            let h = _mm256_loadu_pd(dx.as_ptr().add(i));
+            let d = _mm256_div_pd(_mm256_sub_pd(_mm256_mul_pd(v, uc), _mm256_mul_pd(v, ul)), h);
+            _mm256_storeu_pd(unew.as_mut_ptr().add(i), _mm256_sub_pd(uc, d));
+        }
+        update_loop(vdt, &dx[n..], &u[n..], &mut unew[n..]);
+    }
 
-    //  uniform mesh:
-    let dx = (X_RIGHT - X_LEFT) / ncells as f64;
-    for ie in IFIRST..ilast + 1 {
-        x[ie] = X_LEFT + ie as f64 * dx;
+    pub fn update_simd(vdt: f64, dx: &[f64], u: &[f64], unew: &mut [f64]) {
+        assert!(dx.len() >= unew.len() && u.len() > unew.len());
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx") {
+                return unsafe { update_avx(vdt, dx, u, unew) };
+            }
+        }
+        update_lanes(vdt, dx, u, unew)
     }
 
-    // initial values for diffential equation:
-    let ijump = cmp::max(
-        IFIRST as isize - 1,
-        cmp::min(
-            (ncells as f64 * (JUMP - X_LEFT) / (X_RIGHT - X_LEFT) + 0.5) as isize,
-            (ilast + 1) as isize,
-        ),
-    ) as usize;
-    if options.verbose {
-        println!("ijump : {}", ijump);
+    // the name of the SIMD instruction set used by update_simd
+    pub fn simd_name() -> &'static str {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx") {
+                return "avx";
+            }
+        }
+        "lanes"
     }
 
-    // stable timestep (independent of time for linear advection):
-    let mindx = min_dx(&x);
-    let dt = CFL * mindx / VELOCITY.abs();
+    // a piece of the domain: cells [lo, hi) stored after one halo cell
+    struct Subdomain {
+        lo: usize,
+        hi: usize,
+        u: Vec<f64>,
+        unew: Vec<f64>,
+    }
 
-    //let d = Duration::span(||{
-    let timer = std::time::Instant::now();
-    for r in 0..options.num_runs {
-        if options.verbose {
-            println!("run number : {}", r);
+    fn run_rayon(nparts: usize, nsteps: usize, vdt: f64, statelft: f64, dx: &[f64], u: &mut [f64]) {
+        let ncells = dx.len();
+        if ncells == 0 {
+            // no subdomain to step
+            return;
         }
-
-        // left state to left of jump
-        for ic in IFIRST..ijump - 1 {
-            u[ic + 3] = STATELFT;
+        let size = (ncells + nparts - 1) / nparts;
+        let mut parts = Vec::from_iter((0..ncells).step_by(size).map(|lo| {
+            let hi = (lo + size).min(ncells);
+            Subdomain {
+                lo: lo,
+                hi: hi,
+                u: u[lo + NGHOST - 1..hi + NGHOST].to_vec(),
+                unew: vec![0.0f64; hi - lo + 1],
+            }
+        }));
+        for _ in 0..nsteps {
+            // halo exchange: the last cell of the left neighbour, the inflow state for the first
+            parts[0].u[0] = statelft;
+            for p in 1..parts.len() {
+                parts[p].u[0] = parts[p - 1].u[parts[p - 1].hi - parts[p - 1].lo];
+            }
+            parts.par_iter_mut().for_each(|s| {
+                update_loop(vdt, &dx[s.lo..s.hi], &s.u, &mut s.unew[1..]);
+                mem::swap(&mut s.u, &mut s.unew);
+            });
+        }
+        for s in &parts {
+            u[s.lo + NGHOST..s.hi + NGHOST].copy_from_slice(&s.u[1..]);
         }
+    }
 
-        // volume-weighted average in cell containing jump
-        let frac = (JUMP - X_LEFT - ijump as f64 * dx) / (X_RIGHT - X_LEFT);
-        u[ijump + 3] = STATELFT * frac + STATERGT * (1.0f64 - frac);
+    // `nsteps` upwind steps of `u` with NGHOST ghost cells on each side and inflow
+    // state `statelft`; `nparts` subdomains for Kernel::Rayon
+    pub fn run(
+        kernel: Kernel,
+        nparts: usize,
+        nsteps: usize,
+        vdt: f64,
+        statelft: f64,
+        dx: &[f64],
+        u: &mut [f64],
+    ) {
+        let ncells = dx.len();
+        if kernel == Kernel::Rayon {
+            return run_rayon(nparts, nsteps, vdt, statelft, dx, u);
+        }
+        let update = match kernel {
+            Kernel::Loop => update_loop,
+            Kernel::Windows => update_windows,
+            _ => update_simd,
+        };
+        let (mut cur, mut next) = (u.to_vec(), u.to_vec());
+        for _ in 0..nsteps {
+            cur[NGHOST - 1] = statelft;
+            update(vdt, dx, &cur[NGHOST - 1..ncells + NGHOST], &mut next[NGHOST..ncells + NGHOST]);
+            mem::swap(&mut cur, &mut next);
+        }
+        u[NGHOST..ncells + NGHOST].copy_from_slice(&cur[NGHOST..ncells + NGHOST]);
+    }
 
-        // right state to right of jump
-        for ic in ijump + 1..ilast {
-            u[ic + 3] = STATERGT;
+    #[test]
+    fn kernels_test() {
+        let ncells = 103;
+        let x = super::mesh::perturbed(0.0, 1.0, ncells, 0.3, 11);
+        let dx = Vec::from_iter(x.windows(2).map(|w| w[1] - w[0]));
+        let dt = 0.9f64 * super::min_dx(&x);
+        let nsteps = 50;
+        let mut u0 = vec![0.0f64; ncells + 2 * NGHOST];
+        for ic in 0..ncells {
+            u0[ic + NGHOST] = (7.0f64 * x[ic]).sin() + if x[ic] < 0.3 { 1.0 } else { 0.0 };
         }
 
-        do_computation(
+        // the loop form of the existing benchmark is the reference
+        let mut reference = u0.clone();
+        super::do_computation(
             nsteps,
             ncells,
-            TMAX,
-            IFIRST,
-            ilast,
-            STATELFT,
-            STATERGT,
-            VELOCITY,
+            std::f64::MAX,
+            0,
+            ncells,
+            2.0,
+            2.0,
+            1.0,
             dt,
-            FC,
-            lc,
+            NGHOST,
+            ncells + NGHOST,
             &x,
-            &mut u,
-            true,
+            &mut reference,
+            false,
         );
+        for &kernel in KERNELS.iter() {
+            for &nparts in [1, 3, 7].iter() {
+                let mut u = u0.clone();
+                run(kernel, nparts, nsteps, dt, 2.0, &dx, &mut u);
+                assert!(
+                    u[NGHOST..ncells + NGHOST] == reference[NGHOST..ncells + NGHOST],
+                    "{:?} with {} parts",
+                    kernel,
+                    nparts
+                );
+            }
+            // an empty domain leaves the ghost cells alone
+            let mut ghosts = vec![1.0f64; 2 * NGHOST];
+            run(kernel, 4, nsteps, dt, 2.0, &[], &mut ghosts);
+            assert!(ghosts == vec![1.0f64; 2 * NGHOST], "{:?} without cells", kernel);
+        }
+
+        // the portable lanes agree with the AVX path
+        let mut a = vec![0.0f64; ncells];
+        let mut b = vec![0.0f64; ncells];
+        update_lanes(dt, &dx, &u0[NGHOST - 1..], &mut a);
+        update_simd(dt, &dx, &u0[NGHOST - 1..], &mut b);
+        assert!(a == b);
+    }
+}
+
+// a scalar flux f(u) of the conservation law u_t + f(u)_x = 0
+trait Flux {
+    fn flux(&self, u: f64) -> f64;
+
+    // the characteristic speed f'(u)
+    fn speed(&self, u: f64) -> f64;
+
+    // the increasing points where f'(u) = 0, the flux is monotone between them
+    fn critical_points(&self) -> &[f64] {
+        &[]
+    }
+
+    // the largest |f'(u)| for `u` between `a` and `b`, attained at the ends
+    // for convex or concave fluxes
+    fn max_speed(&self, a: f64, b: f64) -> f64 {
+        self.speed(a).abs().max(self.speed(b).abs())
+    }
+}
+
+struct LinearAdvection {
+    velocity: f64,
+}
+
+impl Flux for LinearAdvection {
+    fn flux(&self, u: f64) -> f64 {
+        self.velocity * u
+    }
+
+    fn speed(&self, _u: f64) -> f64 {
+        self.velocity
+    }
+}
+
+// f(u) = u^2 / 2
+struct Burgers;
+
+impl Flux for Burgers {
+    fn flux(&self, u: f64) -> f64 {
+        0.5f64 * u * u
+    }
+
+    fn speed(&self, u: f64) -> f64 {
+        u
+    }
+
+    fn critical_points(&self) -> &[f64] {
+        &[0.0f64]
+    }
+}
+
+// f(u) = u^2 / (u^2 + a (1 - u)^2), the water saturation `u` in [0, 1]
+// with the viscosity ratio `a`
+struct BuckleyLeverett {
+    viscosity_ratio: f64,
+    // the inflection point, where f' is the largest
+    inflection: f64,
+}
+
+impl BuckleyLeverett {
+    fn new(viscosity_ratio: f64) -> BuckleyLeverett {
+        let mut bl = BuckleyLeverett {
+            viscosity_ratio: viscosity_ratio,
+            inflection: 0.5f64,
+        };
+        // f' is unimodal on [0, 1]: ternary search of its maximum
+        let (mut lo, mut hi) = (0.0f64, 1.0f64);
+        for _ in 0..100 {
+            let m1 = lo + (hi - lo) / 3.0f64;
+            let m2 = hi - (hi - lo) / 3.0f64;
+            if bl.speed(m1) < bl.speed(m2) {
+                lo = m1;
+            } else {
+                hi = m2;
+            }
+        }
+        bl.inflection = 0.5f64 * (lo + hi);
+        bl
+    }
+}
+
+impl Flux for BuckleyLeverett {
+    fn flux(&self, u: f64) -> f64 {
+        let a = self.viscosity_ratio;
+        u * u / (u * u + a * (1.0f64 - u) * (1.0f64 - u))
+    }
+
+    fn speed(&self, u: f64) -> f64 {
+        let a = self.viscosity_ratio;
+        let d = u * u + a * (1.0f64 - u) * (1.0f64 - u);
+        2.0f64 * a * u * (1.0f64 - u) / (d * d)
+    }
+
+    fn critical_points(&self) -> &[f64] {
+        &[0.0f64, 1.0f64]
+    }
+
+    fn max_speed(&self, a: f64, b: f64) -> f64 {
+        let s = self.speed(a).abs().max(self.speed(b).abs());
+        if a.min(b) < self.inflection && self.inflection < a.max(b) {
+            s.max(self.speed(self.inflection))
+        } else {
+            s
+        }
+    }
+}
+
+// the flux values at the critical points strictly between `lo` and `hi`
+fn inner_flux_values<'a>(f: &'a dyn Flux, lo: f64, hi: f64) -> impl Iterator<Item = f64> + 'a {
+    f.critical_points()
+        .iter()
+        .filter(move |&&p| p > lo && p < hi)
+        .map(move |&p| f.flux(p))
+}
+
+// numerical fluxes F(ul, ur) at a cell edge
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NumericalFlux {
+    Godunov,
+    EngquistOsher,
+    LaxFriedrichs,
+    Rusanov,
+}
+
+impl FromStr for NumericalFlux {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<NumericalFlux, String> {
+        match s {
+            "godunov" => Ok(NumericalFlux::Godunov),
+            "eo" => Ok(NumericalFlux::EngquistOsher),
+            "lf" => Ok(NumericalFlux::LaxFriedrichs),
+            "rusanov" => Ok(NumericalFlux::Rusanov),
+            _ => Err(format!(
+                "unknown numerical flux `{}`, expected godunov|eo|lf|rusanov",
+                s
+            )),
+        }
+    }
+}
+
+impl NumericalFlux {
+    // `dx_dt` is the ratio of the mesh width to the timestep, used by Lax-Friedrichs
+    fn flux(&self, f: &dyn Flux, ul: f64, ur: f64, dx_dt: f64) -> f64 {
+        match *self {
+            // exact solution of the Riemann problem at x/t = 0:
+            // min of f over [ul, ur] if ul <= ur, max over [ur, ul] otherwise
+            NumericalFlux::Godunov => {
+                let (fl, fr) = (f.flux(ul), f.flux(ur));
+                if ul <= ur {
+                    inner_flux_values(f, ul, ur).fold(fl.min(fr), f64::min)
+                } else {
+                    inner_flux_values(f, ur, ul).fold(fl.max(fr), f64::max)
+                }
+            }
+            // (f(ul) + f(ur)) / 2 - 1/2 integral of |f'| from ul to ur
+            NumericalFlux::EngquistOsher => {
+                let (lo, hi) = (ul.min(ur), ul.max(ur));
+                let mut variation = 0.0f64;
+                let mut prev = f.flux(lo);
+                for v in inner_flux_values(f, lo, hi).chain(Some(f.flux(hi))) {
+                    variation += (v - prev).abs();
+                    prev = v;
+                }
+                let variation = if ur < ul { -variation } else { variation };
+                0.5f64 * (f.flux(ul) + f.flux(ur) - variation)
+            }
+            NumericalFlux::LaxFriedrichs => {
+                0.5f64 * (f.flux(ul) + f.flux(ur) - dx_dt * (ur - ul))
+            }
+            NumericalFlux::Rusanov => {
+                let alpha = f.max_speed(ul, ur);
+                0.5f64 * (f.flux(ul) + f.flux(ur) - alpha * (ur - ul))
+            }
+        }
+    }
+}
+
+#[test]
+fn numerical_flux_test() {
+    let fluxes: Vec<Box<dyn Flux>> = vec![
+        Box::new(LinearAdvection { velocity: -1.5 }),
+        Box::new(Burgers),
+        Box::new(BuckleyLeverett::new(0.5)),
+    ];
+    let numerical_fluxes = [
+        NumericalFlux::Godunov,
+        NumericalFlux::EngquistOsher,
+        NumericalFlux::LaxFriedrichs,
+        NumericalFlux::Rusanov,
+    ];
+    for f in fluxes.iter() {
+        for nf in numerical_fluxes.iter() {
+            for &u in [0.0f64, 0.25, 0.5, 1.0].iter() {
+                assert!(
+                    (nf.flux(f.as_ref(), u, u, 10.0) - f.flux(u)).abs() < 1e-14,
+                    "{:?} is consistent",
+                    nf
+                );
+            }
+        }
+    }
+
+    // Godunov and Engquist-Osher are upwind for linear advection
+    let linear = LinearAdvection { velocity: 2.0 };
+    for nf in [NumericalFlux::Godunov, NumericalFlux::EngquistOsher].iter() {
+        assert!((nf.flux(&linear, 3.0, 1.0, 10.0) - 6.0).abs() < 1e-14, "{:?}", nf);
+        assert!((nf.flux(&linear, 1.0, 3.0, 10.0) - 2.0).abs() < 1e-14, "{:?}", nf);
+    }
+
+    // Burgers: transonic rarefaction, shocks moving right and left
+    let godunov = NumericalFlux::Godunov;
+    assert!(godunov.flux(&Burgers, -1.0, 1.0, 10.0) == 0.0, "sonic point");
+    assert!(godunov.flux(&Burgers, 2.0, 0.0, 10.0) == 2.0, "right moving shock");
+    assert!(godunov.flux(&Burgers, 0.0, -2.0, 10.0) == 2.0, "left moving shock");
+    assert!(godunov.flux(&Burgers, 1.0, -1.0, 10.0) == 0.5, "stationary shock");
+    assert!(NumericalFlux::EngquistOsher.flux(&Burgers, 1.0, -1.0, 10.0) == 1.0);
+}
+
+// the flux models of the conservation law
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Model {
+    Linear,
+    Burgers,
+    BuckleyLeverett,
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Model, String> {
+        match s {
+            "linear" => Ok(Model::Linear),
+            "burgers" => Ok(Model::Burgers),
+            "buckley-leverett" => Ok(Model::BuckleyLeverett),
+            _ => Err(format!(
+                "unknown model `{}`, expected linear|burgers|buckley-leverett",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RiemannProblem {
+    Shock,
+    Rarefaction,
+}
+
+impl FromStr for RiemannProblem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<RiemannProblem, String> {
+        match s {
+            "shock" => Ok(RiemannProblem::Shock),
+            "rarefaction" => Ok(RiemannProblem::Rarefaction),
+            _ => Err(format!(
+                "unknown Riemann problem `{}`, expected shock|rarefaction",
+                s
+            )),
+        }
+    }
+}
+
+impl Model {
+    fn flux(&self, velocity: f64) -> Box<dyn Flux> {
+        match *self {
+            Model::Linear => Box::new(LinearAdvection { velocity: velocity }),
+            Model::Burgers => Box::new(Burgers),
+            Model::BuckleyLeverett => Box::new(BuckleyLeverett::new(0.5f64)),
+        }
+    }
+
+    // left and right states of the initial jump
+    fn riemann_states(&self, problem: RiemannProblem) -> (f64, f64) {
+        match (*self, problem) {
+            (Model::Linear, RiemannProblem::Shock) => (2.0f64, 0.0f64),
+            (Model::Linear, RiemannProblem::Rarefaction) => (0.0f64, 2.0f64),
+            (Model::Burgers, RiemannProblem::Shock) => (2.0f64, 0.0f64),
+            // transonic, spans the sonic point u = 0
+            (Model::Burgers, RiemannProblem::Rarefaction) => (-0.5f64, 1.0f64),
+            // water flooding: a shock with an attached rarefaction
+            (Model::BuckleyLeverett, RiemannProblem::Shock) => (1.0f64, 0.0f64),
+            // both states in the concave part of the flux
+            (Model::BuckleyLeverett, RiemannProblem::Rarefaction) => (1.0f64, 0.5f64),
+        }
+    }
+}
+
+// boundary condition on one side of the domain
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "BoundarySpec")]
+enum Boundary {
+    // specified value
+    Inflow(f64),
+    // zero gradient, the outgoing wave leaves the domain
+    Outflow,
+    Periodic,
+    // mirror image of the interior cells
+    Reflecting,
+}
+
+// `outflow`, `periodic`, `reflecting` or `{ inflow: value }` in a config file
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BoundarySpec {
+    Name(String),
+    Inflow { inflow: f64 },
+}
+
+impl TryFrom<BoundarySpec> for Boundary {
+    type Error = String;
+
+    fn try_from(spec: BoundarySpec) -> Result<Boundary, String> {
+        match spec {
+            BoundarySpec::Inflow { inflow } => Ok(Boundary::Inflow(inflow)),
+            BoundarySpec::Name(name) => {
+                match name.as_str() {
+                    "outflow" => Ok(Boundary::Outflow),
+                    "periodic" => Ok(Boundary::Periodic),
+                    "reflecting" => Ok(Boundary::Reflecting),
+                    _ => Err(format!(
+                        "unknown boundary condition `{}`, expected {{ inflow: value }}|outflow|periodic|reflecting",
+                        name
+                    )),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoundaryConditions {
+    left: Boundary,
+    right: Boundary,
+}
+
+impl BoundaryConditions {
+    // specified value on the left, outgoing wave on the right
+    fn inflow_outflow(statelft: f64) -> BoundaryConditions {
+        BoundaryConditions {
+            left: Boundary::Inflow(statelft),
+            right: Boundary::Outflow,
+        }
+    }
+}
+
+// fills the ghost cells
+fn apply_boundary_conditions(ncells: usize, bc: &BoundaryConditions, u: &mut [f64]) {
+    let (first, last) = (NGHOST, ncells + NGHOST - 1);
+    for ic in 0..NGHOST {
+        u[first - 1 - ic] = match bc.left {
+            Boundary::Inflow(v) => v,
+            Boundary::Outflow => u[first],
+            Boundary::Periodic => u[last - ic],
+            Boundary::Reflecting => u[first + ic],
+        };
+        u[last + 1 + ic] = match bc.right {
+            Boundary::Inflow(v) => v,
+            Boundary::Outflow => u[last],
+            Boundary::Periodic => u[first + ic],
+            Boundary::Reflecting => u[last - ic],
+        };
+    }
+}
+
+#[test]
+fn boundary_conditions_test() {
+    let interior = [1.0f64, 2.0, 3.0, 4.0, 5.0];
+    let mut u = vec![0.0f64; interior.len() + 2 * NGHOST];
+    u[NGHOST..interior.len() + NGHOST].copy_from_slice(&interior);
+    let mut check = |left: Boundary, right: Boundary, expected: [f64; 4]| {
+        let bc = BoundaryConditions { left: left, right: right };
+        apply_boundary_conditions(interior.len(), &bc, &mut u);
+        let n = u.len();
+        assert!([u[0], u[1], u[n - 2], u[n - 1]] == expected, "{:?}", bc);
+    };
+    check(Boundary::Inflow(7.0), Boundary::Outflow, [7.0, 7.0, 5.0, 5.0]);
+    check(Boundary::Periodic, Boundary::Periodic, [4.0, 5.0, 1.0, 2.0]);
+    check(Boundary::Reflecting, Boundary::Reflecting, [2.0, 1.0, 5.0, 4.0]);
+    check(Boundary::Outflow, Boundary::Inflow(-1.0), [1.0, 1.0, -1.0, -1.0]);
+}
+
+// the largest characteristic speed over the states of neighbouring cells
+fn max_speed(f: &dyn Flux, u: &[f64]) -> f64 {
+    u.windows(2).fold(0.0f64, |s, w| s.max(f.max_speed(w[0], w[1])))
+}
+
+// the limited change of `u` across the cell stored in u[i] of width dx[i],
+// from the divided differences to its neighbours
+fn limited_slope(l: Limiter, dx: &[f64], u: &[f64], i: usize) -> f64 {
+    let dl = (u[i] - u[i - 1]) / (0.5f64 * (dx[i - 1] + dx[i]));
+    let dr = (u[i + 1] - u[i]) / (0.5f64 * (dx[i] + dx[i + 1]));
+    l.slope(dl, dr) * dx[i]
+}
+
+// L(u) = -(F(i+1/2) - F(i-1/2)) / dx(i) with numerical fluxes of the reconstructed
+// edge values, piecewise constant when there is no limiter; `dx` holds the cell
+// widths including the ghost cells
+fn residual(
+    ncells: usize,
+    bc: &BoundaryConditions,
+    f: &dyn Flux,
+    numerical_flux: NumericalFlux,
+    limiter: Option<Limiter>,
+    dt: f64,
+    dx: &[f64],
+    u: &mut [f64],
+    flux: &mut [f64],
+    res: &mut [f64],
+) {
+    apply_boundary_conditions(ncells, bc, u);
+    for ie in 0..ncells + 1 {
+        // edge `ie` lies between the cells stored in u[ie + NGHOST - 1] and u[ie + NGHOST]
+        let (il, ir) = (ie + NGHOST - 1, ie + NGHOST);
+        let (ul, ur) = match limiter {
+            Some(l) => (
+                u[il] + 0.5f64 * limited_slope(l, dx, u, il),
+                u[ir] - 0.5f64 * limited_slope(l, dx, u, ir),
+            ),
+            None => (u[il], u[ir]),
+        };
+        // distance between the centers of the cells sharing the edge
+        let h = 0.5f64 * (dx[il] + dx[ir]);
+        flux[ie] = numerical_flux.flux(f, ul, ur, h / dt);
+    }
+    for ic in 0..ncells {
+        res[ic] = -(flux[ic + 1] - flux[ic]) / dx[ic + NGHOST];
+    }
+}
+
+// method of lines: MUSCL (or first-order) residual advanced by an SSP Runge-Kutta
+// scheme, the timestep recomputed each step from the max wave speed; returns the
+// time reached
+fn do_high_order_computation(
+    nsteps: usize,
+    ncells: usize,
+    tmax: f64,
+    bc: &BoundaryConditions,
+    f: &dyn Flux,
+    numerical_flux: NumericalFlux,
+    cfl: f64,
+    limiter: Option<Limiter>,
+    integrator: TimeIntegrator,
+    x: &[f64],
+    u: &mut [f64],
+) -> f64 {
+    let mut istep = 0;
+    let mut t = 0.0f64;
+    let mut flux = vec![0.0f64; ncells + 1];
+    let mut res = vec![0.0f64; ncells];
+    let mut u0 = vec![0.0f64; ncells];
+    let dx = mesh::padded_widths(x, bc.left == Boundary::Periodic);
+    let mindx = min_dx(x);
+
+    while istep < nsteps && t < tmax {
+        apply_boundary_conditions(ncells, bc, u);
+        let speed = max_speed(f, u);
+        let dt = if speed > 0.0f64 {
+            (cfl * mindx / speed).min(tmax - t)
+        } else {
+            tmax - t
+        };
+        ssp_step(
+            ncells,
+            bc,
+            f,
+            numerical_flux,
+            limiter,
+            integrator,
+            dt,
+            &dx,
+            u,
+            &mut u0,
+            &mut flux,
+            &mut res,
+        );
+        t += dt;
+        istep += 1
+    }
+    t
+}
+
+// one timestep of the SSP Runge-Kutta scheme in Shu-Osher form; `u0`, `flux` and `res`
+// are work arrays of ncells, ncells + 1 and ncells values
+fn ssp_step(
+    ncells: usize,
+    bc: &BoundaryConditions,
+    f: &dyn Flux,
+    numerical_flux: NumericalFlux,
+    limiter: Option<Limiter>,
+    integrator: TimeIntegrator,
+    dt: f64,
+    dx: &[f64],
+    u: &mut [f64],
+    u0: &mut [f64],
+    flux: &mut [f64],
+    res: &mut [f64],
+) {
+    u0.copy_from_slice(&u[NGHOST..ncells + NGHOST]);
+    for &a in integrator.stages() {
+        residual(ncells, bc, f, numerical_flux, limiter, dt, dx, u, flux, res);
+        for ic in 0..ncells {
+            let v = u[ic + NGHOST] + dt * res[ic];
+            u[ic + NGHOST] = a * u0[ic] + (1.0f64 - a) * v;
+        }
+    }
+}
+
+// piecewise constant data with volume-weighted average in the cell containing the jump
+#[cfg(test)]
+fn initial_values(x: &[f64], jump: f64, statelft: f64, statergt: f64, u: &mut [f64]) {
+    let ncells = x.len() - 1;
+    for ic in 0..ncells {
+        let frac = ((jump - x[ic]) / (x[ic + 1] - x[ic])).max(0.0f64).min(1.0f64);
+        u[ic + NGHOST] = statelft * frac + statergt * (1.0f64 - frac);
+    }
+}
+
+// the number of cells across the smeared jump, a measure of numerical dissipation
+fn smeared_cells(u: &[f64], statelft: f64, statergt: f64) -> usize {
+    let (lo, hi) = (statelft.min(statergt), statelft.max(statergt));
+    let eps = 0.01f64 * (hi - lo);
+    u.iter().filter(|&&v| v > lo + eps && v < hi - eps).count()
+}
+
+#[test]
+fn muscl_test() {
+    let ncells = 200;
+    let (x_left, x_right, jump) = (-0.2f64, 1.0f64, 0.0f64);
+    let (statelft, statergt, velocity, tmax, cfl) = (2.0f64, 0.0f64, 1.0f64, 0.8f64, 0.5f64);
+    let dx = (x_right - x_left) / ncells as f64;
+    let x = Vec::from_iter((0..ncells + 1).map(|ie| x_left + ie as f64 * dx));
+    let dt = cfl * min_dx(&x) / velocity;
+    let linear = LinearAdvection { velocity: velocity };
+
+    let run = |limiter: Option<Limiter>, integrator: TimeIntegrator| -> Vec<f64> {
+        let mut u = vec![0.0f64; ncells + 2 * NGHOST];
+        initial_values(&x, jump, statelft, statergt, &mut u);
+        do_high_order_computation(
+            usize::max_value(),
+            ncells,
+            tmax,
+            &BoundaryConditions::inflow_outflow(statelft),
+            &linear,
+            NumericalFlux::Godunov,
+            cfl,
+            limiter,
+            integrator,
+            &x,
+            &mut u,
+        );
+        u[NGHOST..ncells + NGHOST].to_vec()
+    };
+
+    // the method of lines upwind path agrees with the existing one
+    let mut u = vec![0.0f64; ncells + 2 * NGHOST];
+    initial_values(&x, jump, statelft, statergt, &mut u);
+    do_computation(
+        usize::max_value(),
+        ncells,
+        tmax,
+        0,
+        ncells,
+        statelft,
+        statergt,
+        velocity,
+        dt,
+        NGHOST,
+        ncells + NGHOST,
+        &x,
+        &mut u,
+        true,
+    );
+    let upwind = run(None, TimeIntegrator::Euler);
+    for ic in 0..ncells {
+        assert!((upwind[ic] - u[ic + NGHOST]).abs() < 1e-12, "upwind paths differ");
+    }
+
+    let smeared_upwind = smeared_cells(&upwind, statelft, statergt);
+    for &integrator in [TimeIntegrator::SspRk2, TimeIntegrator::SspRk3].iter() {
+        for &l in [Limiter::Minmod, Limiter::Superbee, Limiter::VanLeer, Limiter::MC].iter() {
+            let v = run(Some(l), integrator);
+            // TVD: no new extrema
+            assert!(
+                v.iter().all(|&w| w <= statelft + 1e-12 && w >= statergt - 1e-12),
+                "{:?} {:?} oscillates",
+                l,
+                integrator
+            );
+            let smeared = smeared_cells(&v, statelft, statergt);
+            assert!(
+                smeared < smeared_upwind,
+                "{:?} {:?} smears {} cells, upwind {}",
+                l,
+                integrator,
+                smeared,
+                smeared_upwind
+            );
+        }
+    }
+}
+
+#[test]
+fn nonlinear_flux_test() {
+    let ncells = 240;
+    let (x_left, x_right, jump, tmax) = (-1.0f64, 1.0f64, 0.0f64, 0.3f64);
+    let dx = (x_right - x_left) / ncells as f64;
+    let x = Vec::from_iter((0..ncells + 1).map(|ie| x_left + ie as f64 * dx));
+    let numerical_fluxes = [
+        NumericalFlux::Godunov,
+        NumericalFlux::EngquistOsher,
+        NumericalFlux::LaxFriedrichs,
+        NumericalFlux::Rusanov,
+    ];
+
+    for &model in [Model::Burgers, Model::BuckleyLeverett].iter() {
+        for &problem in [RiemannProblem::Shock, RiemannProblem::Rarefaction].iter() {
+            let (statelft, statergt) = model.riemann_states(problem);
+            let f = model.flux(0.0);
+            for &nf in numerical_fluxes.iter() {
+                let mut u = vec![0.0f64; ncells + 2 * NGHOST];
+                initial_values(&x, jump, statelft, statergt, &mut u);
+                let mass0: f64 = u[NGHOST..ncells + NGHOST].iter().sum::<f64>() * dx;
+                do_high_order_computation(
+                    usize::max_value(),
+                    ncells,
+                    tmax,
+                    &BoundaryConditions::inflow_outflow(statelft),
+                    f.as_ref(),
+                    nf,
+                    0.9,
+                    None,
+                    TimeIntegrator::Euler,
+                    &x,
+                    &mut u,
+                );
+                let v = &u[NGHOST..ncells + NGHOST];
+                let (lo, hi) = (statelft.min(statergt), statelft.max(statergt));
+                assert!(
+                    v.iter().all(|&w| w >= lo - 1e-12 && w <= hi + 1e-12),
+                    "{:?} {:?} {:?}: maximum principle",
+                    model,
+                    problem,
+                    nf
+                );
+                // conservation: the mass changes by the boundary fluxes only
+                // while the waves stay inside the domain
+                let mass: f64 = v.iter().sum::<f64>() * dx;
+                let inflow = tmax * (f.flux(statelft) - f.flux(statergt));
+                assert!(
+                    (mass - mass0 - inflow).abs() < 1e-10,
+                    "{:?} {:?} {:?}: conservation",
+                    model,
+                    problem,
+                    nf
+                );
+            }
+        }
+    }
+
+    // the Burgers shock moves with speed (ul + ur) / 2 = 1
+    let (statelft, statergt) = Model::Burgers.riemann_states(RiemannProblem::Shock);
+    let mut u = vec![0.0f64; ncells + 2 * NGHOST];
+    initial_values(&x, jump, statelft, statergt, &mut u);
+    do_high_order_computation(
+        usize::max_value(),
+        ncells,
+        tmax,
+        &BoundaryConditions::inflow_outflow(statelft),
+        &Burgers,
+        NumericalFlux::Godunov,
+        0.9,
+        None,
+        TimeIntegrator::Euler,
+        &x,
+        &mut u,
+    );
+    let mass: f64 = u[NGHOST..ncells + NGHOST].iter().sum::<f64>() * dx;
+    let shock = x_left + mass / (statelft - statergt);
+    assert!((shock - tmax).abs() < dx, "shock at {}", shock);
+}
+
+// exact solution of the Riemann problem for linear advection and Burgers' equation
+struct RiemannSolution {
+    model: Model,
+    velocity: f64,
+    jump: f64,
+    statelft: f64,
+    statergt: f64,
+}
+
+impl RiemannSolution {
+    // `None` for the models without a closed form solution
+    fn new(model: Model, velocity: f64, jump: f64, statelft: f64, statergt: f64) -> Option<RiemannSolution> {
+        match model {
+            Model::Linear | Model::Burgers => Some(RiemannSolution {
+                model: model,
+                velocity: velocity,
+                jump: jump,
+                statelft: statelft,
+                statergt: statergt,
+            }),
+            Model::BuckleyLeverett => None,
+        }
+    }
+
+    // the increasing points where the solution is not smooth at time `t`:
+    // the discontinuity, or the edges of the rarefaction fan
+    fn breakpoints(&self, t: f64) -> Vec<f64> {
+        let (ul, ur) = (self.statelft, self.statergt);
+        match self.model {
+            Model::Burgers if ul < ur => vec![self.jump + ul * t, self.jump + ur * t],
+            // Rankine-Hugoniot shock speed
+            Model::Burgers => vec![self.jump + 0.5f64 * (ul + ur) * t],
+            _ => vec![self.jump + self.velocity * t],
+        }
+    }
+
+    fn value(&self, x: f64, t: f64) -> f64 {
+        let bp = self.breakpoints(t);
+        if x < bp[0] {
+            self.statelft
+        } else if x >= bp[bp.len() - 1] {
+            self.statergt
+        } else {
+            // inside the Burgers rarefaction fan u = (x - jump) / t
+            (x - self.jump) / t
+        }
+    }
+
+    // the average over [xl, xr]: two-point Gauss rule between the breakpoints,
+    // exact for the piecewise linear solution
+    fn cell_average(&self, xl: f64, xr: f64, t: f64) -> f64 {
+        let mut points = vec![xl];
+        points.extend(self.breakpoints(t).into_iter().filter(|&p| p > xl && p < xr));
+        points.push(xr);
+        let g = 0.5f64 / 3.0f64.sqrt();
+        let integral: f64 = points
+            .windows(2)
+            .map(|w| {
+                let (m, h) = (0.5f64 * (w[0] + w[1]), w[1] - w[0]);
+                0.5f64 * h * (self.value(m - g * h, t) + self.value(m + g * h, t))
+            })
+            .sum();
+        integral / (xr - xl)
+    }
+}
+
+#[test]
+fn riemann_solution_test() {
+    let t = 0.5f64;
+    let rarefaction = RiemannSolution::new(Model::Burgers, 0.0, 0.0, -0.5, 1.0).unwrap();
+    assert!(rarefaction.value(-0.3, t) == -0.5);
+    assert!((rarefaction.value(0.1, t) - 0.2).abs() < 1e-15, "inside the fan");
+    assert!(rarefaction.value(0.6, t) == 1.0);
+    // the fan [-0.25, 0.5] covers the cell partially
+    let avg = rarefaction.cell_average(0.0, 1.0, t);
+    assert!((avg - (0.5f64 * 0.5 * 1.0 + 0.5 * 1.0)).abs() < 1e-15, "average {}", avg);
+
+    let shock = RiemannSolution::new(Model::Burgers, 0.0, 0.0, 2.0, 0.0).unwrap();
+    assert!(shock.breakpoints(t) == vec![0.5], "shock speed 1");
+    assert!((shock.cell_average(0.4, 0.8, t) - 0.5).abs() < 1e-15);
+
+    let linear = RiemannSolution::new(Model::Linear, -1.0, 0.25, 2.0, 0.0).unwrap();
+    assert!(linear.value(-0.3, t) == 2.0 && linear.value(-0.2, t) == 0.0);
+    assert!(RiemannSolution::new(Model::BuckleyLeverett, 0.0, 0.0, 1.0, 0.0).is_none());
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ErrorNorms {
+    l1: f64,
+    l2: f64,
+    linf: f64,
+}
+
+// errors of the cell values `u` against the exact cell averages at time `t`
+fn error_norms(x: &[f64], u: &[f64], exact: &RiemannSolution, t: f64) -> ErrorNorms {
+    let mut norms = ErrorNorms {
+        l1: 0.0f64,
+        l2: 0.0f64,
+        linf: 0.0f64,
+    };
+    for (ic, w) in x.windows(2).enumerate() {
+        let e = (u[ic] - exact.cell_average(w[0], w[1], t)).abs();
+        norms.l1 += e * (w[1] - w[0]);
+        norms.l2 += e * e * (w[1] - w[0]);
+        norms.linf = norms.linf.max(e);
+    }
+    norms.l2 = norms.l2.sqrt();
+    norms
+}
+
+// observed order of accuracy between meshes refined by a factor of 2
+fn observed_order(coarse: f64, fine: f64) -> f64 {
+    (coarse / fine).log2()
+}
+
+// advances `u` by `tmax` with the scheme selected by the options, returns the time reached
+fn compute(
+    options: &Options,
+    problem: &Problem,
+    tmax: f64,
+    nsteps: usize,
+    flux: &dyn Flux,
+    x: &Vec<f64>,
+    u: &mut Vec<f64>,
+) -> f64 {
+    let ncells = x.len() - 1;
+    let limiter = match options.scheme {
+        Scheme::Upwind => None,
+        Scheme::Muscl => Some(options.limiter),
+    };
+
+    match problem.boundary {
+        // the upwind benchmark path for linear advection with velocity > 0
+        BoundaryConditions {
+            left: Boundary::Inflow(statelft),
+            right: Boundary::Outflow,
+        } if problem.model == Model::Linear && problem.velocity > 0.0f64 && limiter.is_none() &&
+                 options.integrator == TimeIntegrator::Euler &&
+                 options.numerical_flux == NumericalFlux::Godunov => {
+            // stable timestep (independent of time for linear advection):
+            let dt = problem.cfl * min_dx(x) / problem.velocity;
+            do_computation(
+                nsteps,
+                ncells,
+                tmax,
+                0,
+                ncells,
+                statelft,
+                statelft,
+                problem.velocity,
+                dt,
+                NGHOST,
+                ncells + NGHOST,
+                x,
+                u,
+                true,
+            )
+        }
+        _ => {
+            do_high_order_computation(
+                nsteps,
+                ncells,
+                tmax,
+                &problem.boundary,
+                flux,
+                options.numerical_flux,
+                problem.cfl,
+                limiter,
+                options.integrator,
+                x,
+                u,
+            )
+        }
+    }
+}
+
+// errors at tmax on `levels` meshes starting from `num_cells`, each twice as fine
+// times `num_steps` steps of each upwind kernel variant on the mesh `x`, best of
+// `num_runs`, and checks them against the loop form of do_computation
+fn benchmark(options: &Options, problem: &Problem, x: &Vec<f64>) -> Result<(), String> {
+    let statelft = match problem.boundary {
+        BoundaryConditions {
+            left: Boundary::Inflow(statelft),
+            right: Boundary::Outflow,
+        } if problem.model == Model::Linear && problem.velocity > 0.0f64 => statelft,
+        _ => {
+            return Err(
+                "the benchmark needs linear advection with velocity > 0 and inflow/outflow boundaries"
+                    .to_string(),
+            )
+        }
+    };
+    let ncells = x.len() - 1;
+    let nsteps = options.num_steps;
+    let dt = problem.cfl * min_dx(x) / problem.velocity;
+    let dx = Vec::from_iter(x.windows(2).map(|w| w[1] - w[0]));
+    let mut u0 = vec![0.0f64; ncells + 2 * NGHOST];
+    problem.initial_values(x, &mut u0);
+
+    let mut reference = u0.clone();
+    do_computation(
+        nsteps,
+        ncells,
+        std::f64::MAX,
+        0,
+        ncells,
+        statelft,
+        statelft,
+        problem.velocity,
+        dt,
+        NGHOST,
+        ncells + NGHOST,
+        x,
+        &mut reference,
+        false,
+    );
+    let scale = reference.iter().fold(0.0f64, |s, v| s.max(v.abs()));
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let nparts = pool.current_num_threads();
+    println!(
+        "{} build, {} cells, {} steps, {} runs, {} rayon threads, simd : {}",
+        if cfg!(debug_assertions) { "debug" } else { "release" },
+        ncells,
+        nsteps,
+        options.num_runs,
+        nparts,
+        kernels::simd_name()
+    );
+    println!("{:>8} {:>12} {:>16} {:>12}  check", "kernel", "time [s]", "cell-updates/s", "max |diff|");
+    let mut mismatch = false;
+    for &kernel in kernels::KERNELS.iter() {
+        let mut best = std::f64::MAX;
+        let mut u = u0.clone();
+        for _ in 0..options.num_runs.max(1) {
+            u.copy_from_slice(&u0);
+            let timer = std::time::Instant::now();
+            pool.install(|| kernels::run(kernel, nparts, nsteps, problem.velocity * dt, statelft, &dx, &mut u));
+            let d = timer.elapsed();
+            best = best.min(d.as_secs() as f64 + d.subsec_nanos() as f64 / 1.0e9f64);
+        }
+        let diff = u[NGHOST..ncells + NGHOST]
+            .iter()
+            .zip(&reference[NGHOST..ncells + NGHOST])
+            .fold(0.0f64, |s, (a, b)| s.max((a - b).abs()));
+        let check = if diff == 0.0f64 {
+            "bitwise"
+        } else if diff <= 1e-12 * scale {
+            "tolerance"
+        } else {
+            mismatch = true;
+            "MISMATCH"
+        };
+        println!(
+            "{:>8} {:>12.6} {:>16.4e} {:>12.4e}  {}",
+            format!("{:?}", kernel).to_lowercase(),
+            best,
+            (ncells * nsteps) as f64 / best,
+            diff,
+            check
+        );
+    }
+    if mismatch {
+        return Err("kernel results differ".to_string());
+    }
+    Ok(())
+}
+
+fn convergence_study(
+    options: &Options,
+    problem: &Problem,
+    exact: &RiemannSolution,
+    flux: &dyn Flux,
+) -> Result<Vec<(usize, ErrorNorms)>, String> {
+    (0..options.levels)
+        .map(|level| {
+            let x = build_mesh(options, problem, level)?;
+            let ncells = x.len() - 1;
+            let mut u = vec![0.0f64; ncells + 2 * NGHOST];
+            problem.initial_values(&x, &mut u);
+            let t = compute(options, problem, problem.tmax, usize::max_value(), flux, &x, &mut u);
+            Ok((ncells, error_norms(&x, &u[NGHOST..ncells + NGHOST], exact, t)))
+        })
+        .collect()
+}
+
+// the mesh of the problem domain selected by the options, refined `level` times:
+// `num_cells << level` cells, or the cells of the node file split `level` times
+fn build_mesh(options: &Options, problem: &Problem, level: usize) -> Result<Vec<f64>, String> {
+    let (x_left, x_right) = problem.domain;
+    let ncells = options.num_cells << level;
+    if !options.mesh_file.is_empty() {
+        let mut x = mesh::read_nodes(&options.mesh_file).map_err(|e| {
+            format!("{}: {}", options.mesh_file, e)
+        })?;
+        for _ in 0..level {
+            x = mesh::refined(&x);
+        }
+        return Ok(x);
+    }
+    if ncells < NGHOST {
+        return Err(format!("at least {} cells needed", NGHOST));
+    }
+    match options.mesh {
+        MeshKind::Uniform => Ok(mesh::uniform(x_left, x_right, ncells)),
+        MeshKind::Stretched if options.stretch > 0.0f64 => {
+            Ok(mesh::stretched(x_left, x_right, ncells, options.stretch))
+        }
+        // the levels bisect the perturbed mesh of level 0, a new random mesh per level
+        // would not be a refinement
+        MeshKind::Perturbed if options.perturbation >= 0.0f64 && options.perturbation < 0.5f64 => {
+            let mut x = mesh::perturbed(
+                x_left,
+                x_right,
+                options.num_cells,
+                options.perturbation,
+                options.seed,
+            );
+            for _ in 0..level {
+                x = mesh::refined(&x);
+            }
+            Ok(x)
+        }
+        MeshKind::Stretched => Err("the stretch ratio must be positive".to_string()),
+        MeshKind::Perturbed => Err("the perturbation must be in [0, 0.5)".to_string()),
+    }
+}
+
+fn print_convergence_table(results: &[(usize, ErrorNorms)]) {
+    println!(
+        "{:>8} {:>12} {:>6} {:>12} {:>6} {:>12} {:>6}",
+        "cells",
+        "L1",
+        "order",
+        "L2",
+        "order",
+        "Linf",
+        "order"
+    );
+    for (k, &(ncells, e)) in results.iter().enumerate() {
+        let order = |f: fn(&ErrorNorms) -> f64| if k == 0 {
+            format!("{:>6}", "-")
+        } else {
+            format!("{:>6.2}", observed_order(f(&results[k - 1].1), f(&e)))
+        };
+        println!(
+            "{:>8} {:>12.4e} {} {:>12.4e} {} {:>12.4e} {}",
+            ncells,
+            e.l1,
+            order(|e| e.l1),
+            e.l2,
+            order(|e| e.l2),
+            e.linf,
+            order(|e| e.linf)
+        );
+    }
+}
+
+#[test]
+fn convergence_test() {
+    let mut options = Options {
+        num_steps: 0,
+        num_cells: 100,
+        num_runs: 1,
+        convergence: true,
+        levels: 4,
+        ..Options::default()
+    };
+    let mut problem = Problem::riemann(options.model, options.riemann_problem);
+    problem.cfl = 0.5;
+    let exact = problem.exact_solution().unwrap();
+    let flux = problem.flux();
+
+    // first-order schemes converge as dx^(1/2) in L1 for a jump
+    let upwind = convergence_study(&options, &problem, &exact, flux.as_ref()).unwrap();
+    let n = upwind.len();
+    let upwind_order = observed_order(upwind[n - 2].1.l1, upwind[n - 1].1.l1);
+    assert!(upwind_order > 0.4 && upwind_order < 0.6, "upwind order {}", upwind_order);
+    assert!(upwind.iter().all(|&(_, e)| e.linf <= exact.statelft - exact.statergt));
+
+    options.scheme = Scheme::Muscl;
+    options.integrator = TimeIntegrator::SspRk2;
+    let muscl = convergence_study(&options, &problem, &exact, flux.as_ref()).unwrap();
+    let muscl_order = observed_order(muscl[n - 2].1.l1, muscl[n - 1].1.l1);
+    assert!(muscl_order > upwind_order, "MUSCL order {}", muscl_order);
+    assert!(muscl[n - 1].1.l1 < upwind[n - 1].1.l1);
+
+    // Burgers: shocks are captured within a few cells, the L1 error is first order
+    options.scheme = Scheme::Upwind;
+    options.integrator = TimeIntegrator::Euler;
+    let mut problem = Problem::riemann(Model::Burgers, options.riemann_problem);
+    problem.cfl = 0.5;
+    let exact = problem.exact_solution().unwrap();
+    let burgers = convergence_study(&options, &problem, &exact, problem.flux().as_ref()).unwrap();
+    let order = observed_order(burgers[n - 2].1.l1, burgers[n - 1].1.l1);
+    assert!(order > 0.8, "Burgers shock order {}", order);
+}
+
+// initial data u(x) given as an arithmetic expression of `x` with
+// + - * / ^, parentheses, `pi` and the functions below
+#[derive(Clone)]
+enum Expr {
+    Number(f64),
+    X,
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Call(&'static str, fn(f64) -> f64, Box<Expr>),
+}
+
+// fully parenthesized infix form
+impl fmt::Debug for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Expr::Number(v) => write!(f, "{}", v),
+            Expr::X => write!(f, "x"),
+            Expr::Neg(ref a) => write!(f, "(-{:?})", a),
+            Expr::Add(ref a, ref b) => write!(f, "({:?} + {:?})", a, b),
+            Expr::Sub(ref a, ref b) => write!(f, "({:?} - {:?})", a, b),
+            Expr::Mul(ref a, ref b) => write!(f, "({:?} * {:?})", a, b),
+            Expr::Div(ref a, ref b) => write!(f, "({:?} / {:?})", a, b),
+            Expr::Pow(ref a, ref b) => write!(f, "({:?} ^ {:?})", a, b),
+            Expr::Call(name, _, ref a) => write!(f, "{}({:?})", name, a),
+        }
+    }
+}
+
+fn heaviside(x: f64) -> f64 {
+    if x < 0.0f64 { 0.0f64 } else { 1.0f64 }
+}
+
+fn function(name: &str) -> Option<(&'static str, fn(f64) -> f64)> {
+    let f: (&'static str, fn(f64) -> f64) = match name {
+        "sin" => ("sin", f64::sin),
+        "cos" => ("cos", f64::cos),
+        "tan" => ("tan", f64::tan),
+        "exp" => ("exp", f64::exp),
+        "ln" => ("ln", f64::ln),
+        "sqrt" => ("sqrt", f64::sqrt),
+        "abs" => ("abs", f64::abs),
+        "tanh" => ("tanh", f64::tanh),
+        "step" => ("step", heaviside),
+        _ => return None,
+    };
+    Some(f)
+}
+
+// recursive descent parser:
+//   expr  := term (('+' | '-') term)*
+//   term  := unary (('*' | '/') unary)*
+//   unary := '-' unary | power
+//   power := atom ('^' unary)?
+//   atom  := number | 'x' | 'pi' | name '(' expr ')' | '(' expr ')'
+struct ExprParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn peek(&mut self) -> Option<char> {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected `{}` at column {}", c, self.pos + 1))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        loop {
+            match self.peek() {
+                Some('+') => {
+                    self.pos += 1;
+                    lhs = Expr::Add(Box::new(lhs), Box::new(self.term()?));
+                }
+                Some('-') => {
+                    self.pos += 1;
+                    lhs = Expr::Sub(Box::new(lhs), Box::new(self.term()?));
+                }
+                _ => return Ok(lhs),
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            match self.peek() {
+                Some('*') => {
+                    self.pos += 1;
+                    lhs = Expr::Mul(Box::new(lhs), Box::new(self.unary()?));
+                }
+                Some('/') => {
+                    self.pos += 1;
+                    lhs = Expr::Div(Box::new(lhs), Box::new(self.unary()?));
+                }
+                _ => return Ok(lhs),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some('-') {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let base = self.atom()?;
+        if self.peek() == Some('^') {
+            self.pos += 1;
+            return Ok(Expr::Pow(Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let start = self.pos;
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let e = self.expr()?;
+                self.expect(')')?;
+                Ok(e)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let start = self.pos;
+                while self.pos < self.chars.len() &&
+                    (self.chars[self.pos].is_ascii_digit() || self.chars[self.pos] == '.' ||
+                         ((self.chars[self.pos] == 'e' || self.chars[self.pos] == 'E') &&
+                              self.pos + 1 < self.chars.len() &&
+                              (self.chars[self.pos + 1].is_ascii_digit() ||
+                                   self.chars[self.pos + 1] == '-')) ||
+                         (self.chars[self.pos] == '-' &&
+                              (self.chars[self.pos - 1] == 'e' || self.chars[self.pos - 1] == 'E')))
+                {
+                    self.pos += 1;
+                }
+                let token: String = self.chars[start..self.pos].iter().collect();
+                token.parse().map(Expr::Number).map_err(|_| {
+                    format!("invalid number `{}` at column {}", token, start + 1)
+                })
+            }
+            Some(c) if c.is_alphabetic() => {
+                let start = self.pos;
+                while self.pos < self.chars.len() && self.chars[self.pos].is_alphanumeric() {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                match name.as_str() {
+                    "x" => Ok(Expr::X),
+                    "pi" => Ok(Expr::Number(std::f64::consts::PI)),
+                    _ => {
+                        let (name, f) = function(&name).ok_or_else(|| {
+                            format!("unknown name `{}` at column {}", name, start + 1)
+                        })?;
+                        self.expect('(')?;
+                        let arg = self.expr()?;
+                        self.expect(')')?;
+                        Ok(Expr::Call(name, f, Box::new(arg)))
+                    }
+                }
+            }
+            Some(c) => Err(format!("unexpected `{}` at column {}", c, start + 1)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+impl Expr {
+    fn parse(text: &str) -> Result<Expr, String> {
+        let mut parser = ExprParser {
+            chars: text.chars().collect(),
+            pos: 0,
+        };
+        let e = parser.expr()?;
+        match parser.peek() {
+            None => Ok(e),
+            Some(c) => Err(format!("unexpected `{}` at column {}", c, parser.pos + 1)),
+        }
+    }
+
+    fn eval(&self, x: f64) -> f64 {
+        match *self {
+            Expr::Number(v) => v,
+            Expr::X => x,
+            Expr::Neg(ref a) => -a.eval(x),
+            Expr::Add(ref a, ref b) => a.eval(x) + b.eval(x),
+            Expr::Sub(ref a, ref b) => a.eval(x) - b.eval(x),
+            Expr::Mul(ref a, ref b) => a.eval(x) * b.eval(x),
+            Expr::Div(ref a, ref b) => a.eval(x) / b.eval(x),
+            Expr::Pow(ref a, ref b) => a.eval(x).powf(b.eval(x)),
+            Expr::Call(_, f, ref a) => f(a.eval(x)),
+        }
+    }
+}
+
+#[test]
+fn expr_test() {
+    let eval = |text: &str, x: f64| Expr::parse(text).unwrap().eval(x);
+    assert!(eval("1 + 2 * 3", 0.0) == 7.0);
+    assert!(eval("(1 + 2) * 3", 0.0) == 9.0);
+    assert!(eval("2 ^ 3 ^ 2", 0.0) == 512.0, "right associative");
+    assert!(eval("-x^2", 3.0) == -9.0, "unary minus binds weaker than ^");
+    assert!(eval("1 - 2 - 3", 0.0) == -4.0, "left associative");
+    assert!(eval("2.5e-1 * 4", 0.0) == 1.0);
+    assert!((eval("exp(-100*(x-0.3)^2)", 0.3) - 1.0).abs() < 1e-15);
+    assert!((eval("sin(pi*x)", 0.5) - 1.0).abs() < 1e-15);
+    assert!(eval("2*step(-x) + step(x - 1)", -0.5) == 2.0);
+    assert!(format!("{:?}", Expr::parse("-x^2 + sin(2*x)").unwrap()) == "((-(x ^ 2)) + sin((2 * x)))");
+    assert!(Expr::parse("sin x").is_err());
+    assert!(Expr::parse("1 +").is_err());
+    assert!(Expr::parse("foo(x)").is_err());
+    assert!(Expr::parse("(x").is_err());
+    assert!(Expr::parse("x x").is_err());
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "InitialSpec")]
+enum InitialData {
+    // `states` between increasing `breakpoints`, one more state than breakpoints
+    Piecewise {
+        breakpoints: Vec<f64>,
+        states: Vec<f64>,
+    },
+    Expression(Expr),
+}
+
+// `{ breakpoints: [...], states: [...] }` or `{ expression: "..." }` in a config file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InitialSpec {
+    breakpoints: Option<Vec<f64>>,
+    states: Option<Vec<f64>>,
+    expression: Option<String>,
+}
+
+impl TryFrom<InitialSpec> for InitialData {
+    type Error = String;
+
+    fn try_from(spec: InitialSpec) -> Result<InitialData, String> {
+        match spec {
+            InitialSpec {
+                breakpoints: None,
+                states: None,
+                expression: Some(text),
+            } => {
+                Expr::parse(&text).map(InitialData::Expression).map_err(|e| {
+                    format!("expression `{}`: {}", text, e)
+                })
+            }
+            InitialSpec {
+                breakpoints,
+                states: Some(states),
+                expression: None,
+            } => {
+                let breakpoints = breakpoints.unwrap_or_default();
+                if states.len() != breakpoints.len() + 1 {
+                    return Err(format!(
+                        "{} breakpoints need {} states, found {}",
+                        breakpoints.len(),
+                        breakpoints.len() + 1,
+                        states.len()
+                    ));
+                }
+                if breakpoints.windows(2).any(|w| !(w[0] < w[1])) {
+                    return Err("breakpoints are not increasing".to_string());
+                }
+                Ok(InitialData::Piecewise {
+                    breakpoints: breakpoints,
+                    states: states,
+                })
+            }
+            _ => Err("initial data needs either `states` or `expression`".to_string()),
+        }
+    }
+}
+
+fn default_velocity() -> f64 {
+    VELOCITY
+}
+
+fn default_cfl() -> f64 {
+    CFL
+}
+
+// problem description read from a YAML or TOML file, e.g.
+//
+//   model: burgers            # linear | burgers | buckley-leverett
+//   velocity: 1.0             # linear advection only
+//   domain: [-0.2, 1.0]
+//   tmax: 0.8
+//   cfl: 0.9
+//   initial:
+//     breakpoints: [0.0]
+//     states: [2.0, 0.0]      # or `expression: "exp(-100*(x-0.3)^2)"`
+//   boundary:
+//     left: { inflow: 2.0 }   # inflow | outflow | periodic | reflecting
+//     right: outflow
+//   snapshots: [0.4, 0.8]     # output times
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Problem {
+    model: Model,
+    #[serde(default = "default_velocity")]
+    velocity: f64,
+    domain: (f64, f64),
+    tmax: f64,
+    #[serde(default = "default_cfl")]
+    cfl: f64,
+    initial: InitialData,
+    boundary: BoundaryConditions,
+    // output times, tmax if empty
+    #[serde(default)]
+    snapshots: Vec<f64>,
+}
+
+impl Problem {
+    // the built-in Riemann problem of `model` with inflow on the left
+    fn riemann(model: Model, problem: RiemannProblem) -> Problem {
+        let (statelft, statergt) = model.riemann_states(problem);
+        Problem {
+            model: model,
+            velocity: VELOCITY,
+            domain: (X_LEFT, X_RIGHT),
+            tmax: TMAX,
+            cfl: CFL,
+            initial: InitialData::Piecewise {
+                breakpoints: vec![JUMP],
+                states: vec![statelft, statergt],
+            },
+            boundary: BoundaryConditions::inflow_outflow(statelft),
+            snapshots: Vec::new(),
+        }
+    }
+
+    fn parse(text: &str, toml: bool) -> Result<Problem, String> {
+        let problem: Problem = if toml {
+            self::toml::from_str(text).map_err(|e| e.to_string())?
+        } else {
+            serde_yaml::from_str(text).map_err(|e| e.to_string())?
+        };
+        problem.validate()?;
+        Ok(problem)
+    }
+
+    // TOML for the `.toml` extension, YAML otherwise
+    fn read(fname: &str) -> Result<Problem, String> {
+        let mut text = String::new();
+        File::open(fname)
+            .and_then(|mut f| f.read_to_string(&mut text))
+            .map_err(|e| e.to_string())?;
+        let toml = Path::new(fname).extension().map_or(false, |e| e == "toml");
+        Problem::parse(&text, toml)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(self.domain.0 < self.domain.1) {
+            return Err(format!("empty domain [{}, {}]", self.domain.0, self.domain.1));
+        }
+        if !(self.tmax > 0.0f64) || !(self.cfl > 0.0f64) {
+            return Err("tmax and cfl must be positive".to_string());
+        }
+        if (self.boundary.left == Boundary::Periodic) != (self.boundary.right == Boundary::Periodic) {
+            return Err("periodic boundary conditions on one side only".to_string());
+        }
+        if self.snapshots.iter().any(|&t| !(t >= 0.0f64)) {
+            return Err("negative snapshot time".to_string());
+        }
+        Ok(())
+    }
+
+    fn flux(&self) -> Box<dyn Flux> {
+        self.model.flux(self.velocity)
+    }
+
+    // cell averages: exact for piecewise constant data, three-point Gauss rule
+    // for expressions
+    fn initial_values(&self, x: &[f64], u: &mut [f64]) {
+        for (ic, w) in x.windows(2).enumerate() {
+            let (xl, xr) = (w[0], w[1]);
+            u[ic + NGHOST] = match self.initial {
+                InitialData::Piecewise {
+                    ref breakpoints,
+                    ref states,
+                } => {
+                    let mut sum = 0.0f64;
+                    for (k, &state) in states.iter().enumerate() {
+                        let lo = if k == 0 { xl } else { breakpoints[k - 1].max(xl) };
+                        let hi = if k == breakpoints.len() { xr } else { breakpoints[k].min(xr) };
+                        if hi > lo {
+                            sum += state * (hi - lo);
+                        }
+                    }
+                    sum / (xr - xl)
+                }
+                InitialData::Expression(ref e) => {
+                    let (m, h) = (0.5f64 * (xl + xr), 0.5f64 * (xr - xl));
+                    let g = (0.6f64).sqrt();
+                    (5.0f64 * e.eval(m - g * h) + 8.0f64 * e.eval(m) + 5.0f64 * e.eval(m + g * h)) /
+                        18.0f64
+                }
+            };
+        }
+    }
+
+    // the exact solution of a single jump away from periodic boundaries
+    fn exact_solution(&self) -> Option<RiemannSolution> {
+        match self.initial {
+            InitialData::Piecewise {
+                ref breakpoints,
+                ref states,
+            } if breakpoints.len() == 1 && self.boundary.left != Boundary::Periodic => {
+                RiemannSolution::new(self.model, self.velocity, breakpoints[0], states[0], states[1])
+            }
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn problem_test() {
+    let yaml = "
+model: burgers
+domain: [-1.0, 1.0]
+tmax: 0.5
+initial:
+  breakpoints: [-0.5, 0.0]
+  states: [0.0, 1.0, 0.0]
+boundary:
+  left: { inflow: 0.0 }
+  right: outflow
+";
+    let problem = Problem::parse(yaml, false).unwrap();
+    assert!(problem.model == Model::Burgers && problem.cfl == CFL);
+    assert!(problem.boundary == BoundaryConditions::inflow_outflow(0.0));
+    assert!(problem.exact_solution().is_none(), "two jumps");
+    let x = build_mesh(&Options { num_cells: 8, ..Options::default() }, &problem, 0).unwrap();
+    let mut u = vec![0.0f64; 8 + 2 * NGHOST];
+    problem.initial_values(&x, &mut u);
+    assert!(u[NGHOST..8 + NGHOST] == [0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+
+    let toml = r#"
+model = "linear"
+velocity = -1.0
+domain = [0.0, 1.0]
+tmax = 1.0
+cfl = 0.5
+initial = { expression = "sin(2*pi*x)" }
+boundary = { left = "periodic", right = "periodic" }
+"#;
+    let problem = Problem::parse(toml, true).unwrap();
+    assert!(problem.velocity == -1.0 && problem.boundary.right == Boundary::Periodic);
+    assert!(problem.exact_solution().is_none());
+
+    // one period of periodic advection returns the initial data
+    let ncells = 100;
+    let options = Options {
+        num_steps: 0,
+        num_cells: ncells,
+        num_runs: 1,
+        scheme: Scheme::Muscl,
+        limiter: Limiter::MC,
+        integrator: TimeIntegrator::SspRk3,
+        model: problem.model,
+        levels: 1,
+        ..Options::default()
+    };
+    let x = build_mesh(&options, &problem, 0).unwrap();
+    let mut u = vec![0.0f64; ncells + 2 * NGHOST];
+    problem.initial_values(&x, &mut u);
+    let u0 = u.clone();
+    let t = compute(
+        &options,
+        &problem,
+        problem.tmax,
+        usize::max_value(),
+        problem.flux().as_ref(),
+        &x,
+        &mut u,
+    );
+    assert!(t == 1.0);
+    let mass = |u: &[f64]| u[NGHOST..ncells + NGHOST].iter().sum::<f64>();
+    assert!((mass(&u) - mass(&u0)).abs() < 1e-12, "periodic conservation");
+    for ic in NGHOST..ncells + NGHOST {
+        assert!((u[ic] - u0[ic]).abs() < 0.02, "cell {}: {} {}", ic, u[ic], u0[ic]);
+    }
+
+    let errors = [
+        ("model: heat\ndomain: [0, 1]\ntmax: 1\ninitial: { expression: x }\nboundary: { left: outflow, right: outflow }", "model"),
+        ("model: linear\ndomain: [1, 0]\ntmax: 1\ninitial: { expression: x }\nboundary: { left: outflow, right: outflow }", "empty domain"),
+        ("model: linear\ndomain: [0, 1]\ntmax: 1\ninitial: { expression: \"x +\" }\nboundary: { left: outflow, right: outflow }", "expression"),
+        ("model: linear\ndomain: [0, 1]\ntmax: 1\ninitial: { breakpoints: [0.5], states: [1] }\nboundary: { left: outflow, right: outflow }", "states"),
+        ("model: linear\ndomain: [0, 1]\ntmax: 1\ninitial: { expression: x }\nboundary: { left: periodic, right: outflow }", "periodic"),
+        ("model: linear\ndomain: [0, 1]\ntmax: 1\ninitial: { expression: x }\nboundary: { left: wall, right: outflow }", "boundary"),
+        ("model: linear\ndomain: [0, 1]\ntmax: 1\ninitial: { expression: x, states: [1] }\nboundary: { left: outflow, right: outflow }", "initial data"),
+        ("model: linear\ndomain: [0, 1]\ntmax: 1\ninitial: { expression: x }\nboundary: { left: outflow, right: outflow }\nvelocit: 1", "unknown field"),
+    ];
+    for &(text, what) in errors.iter() {
+        assert!(Problem::parse(text, false).is_err(), "{}", what);
+    }
+}
+
+#[test]
+fn nonuniform_mesh_test() {
+    let text = "
+        model: linear
+        domain: [0.0, 1.0]
+        tmax: 1.0
+        cfl: 0.5
+        initial:
+          expression: 1 + 0.5*sin(2*pi*x)
+        boundary:
+          left: periodic
+          right: periodic
+    ";
+    let problem = Problem::parse(&unindent(text), false).unwrap();
+    let mut options = Options {
+        num_steps: 0,
+        num_cells: 40,
+        num_runs: 1,
+        scheme: Scheme::Muscl,
+        integrator: TimeIntegrator::SspRk3,
+        levels: 3,
+        mesh: MeshKind::Stretched,
+        stretch: 3.0,
+        perturbation: 0.3,
+        seed: 5,
+        ..Options::default()
+    };
+
+    // one period later the cell averages are back to the initial ones
+    for &kind in [MeshKind::Stretched, MeshKind::Perturbed].iter() {
+        options.mesh = kind;
+        let errors = Vec::from_iter((0..options.levels).map(|level| {
+            let x = build_mesh(&options, &problem, level).unwrap();
+            let ncells = x.len() - 1;
+            let mut u0 = vec![0.0f64; ncells + 2 * NGHOST];
+            problem.initial_values(&x, &mut u0);
+            let mut u = u0.clone();
+            let flux = problem.flux();
+            let t = compute(&options, &problem, problem.tmax, usize::max_value(), flux.as_ref(), &x, &mut u);
+            assert!((t - problem.tmax).abs() < 1e-12);
+            let mass = |u: &[f64]| {
+                x.windows(2)
+                    .enumerate()
+                    .map(|(ic, w)| u[ic + NGHOST] * (w[1] - w[0]))
+                    .sum::<f64>()
+            };
+            assert!((mass(&u) - mass(&u0)).abs() < 1e-12, "{:?} mesh loses mass", kind);
+            x.windows(2)
+                .enumerate()
+                .map(|(ic, w)| (u[ic + NGHOST] - u0[ic + NGHOST]).abs() * (w[1] - w[0]))
+                .sum::<f64>()
+        }));
+        for l in 1..errors.len() {
+            let order = observed_order(errors[l - 1], errors[l]);
+            assert!(order > 1.2, "{:?} mesh order {}", kind, order);
+        }
+    }
+
+    // the perturbed levels are nested
+    let coarse = build_mesh(&options, &problem, 0).unwrap();
+    let fine = build_mesh(&options, &problem, 1).unwrap();
+    assert!(fine.iter().step_by(2).eq(coarse.iter()), "level 1 bisects level 0");
+
+    // a jump still converges at dx^(1/2) on a stretched mesh
+    let mut problem = Problem::riemann(Model::Linear, RiemannProblem::Shock);
+    problem.cfl = 0.5;
+    let exact = problem.exact_solution().unwrap();
+    options.mesh = MeshKind::Stretched;
+    options.scheme = Scheme::Upwind;
+    options.integrator = TimeIntegrator::Euler;
+    options.levels = 4;
+    let results = convergence_study(&options, &problem, &exact, problem.flux().as_ref()).unwrap();
+    let n = results.len();
+    let order = observed_order(results[n - 2].1.l1, results[n - 1].1.l1);
+    assert!(order > 0.4 && order < 0.6, "stretched upwind order {}", order);
+
+    options.mesh = MeshKind::Perturbed;
+    options.perturbation = 0.5;
+    assert!(build_mesh(&options, &problem, 0).is_err());
+}
+
+fn cell_centers(x: &[f64]) -> Vec<f64> {
+    Vec::from_iter(x.windows(2).map(|w| 0.5f64 * (w[0] + w[1])))
+}
+
+// one row per cell: t, cell center, computed and exact cell averages
+fn write_csv<W: Write>(w: &mut W, t: f64, x: &[f64], u: &[f64], exact: Option<&[f64]>) -> io::Result<()> {
+    match exact {
+        Some(_) => writeln!(w, "t,x,u,exact")?,
+        None => writeln!(w, "t,x,u")?,
+    }
+    for (ic, xc) in cell_centers(x).iter().enumerate() {
+        write!(w, "{},{},{}", t, xc, u[ic])?;
+        if let Some(e) = exact {
+            write!(w, ",{}", e[ic])?;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+// legacy VTK 1D rectilinear grid with cell data, the time as field data
+fn write_vtk<W: Write>(w: &mut W, t: f64, x: &[f64], u: &[f64], exact: Option<&[f64]>) -> io::Result<()> {
+    let ncells = x.len() - 1;
+    writeln!(w, "# vtk DataFile Version 3.0")?;
+    writeln!(w, "scalar conservation law t = {}", t)?;
+    writeln!(w, "ASCII")?;
+    writeln!(w, "DATASET RECTILINEAR_GRID")?;
+    writeln!(w, "FIELD FieldData 1")?;
+    writeln!(w, "TIME 1 1 double")?;
+    writeln!(w, "{}", t)?;
+    writeln!(w, "DIMENSIONS {} 1 1", x.len())?;
+    writeln!(w, "X_COORDINATES {} double", x.len())?;
+    for xe in x {
+        writeln!(w, "{}", xe)?;
+    }
+    writeln!(w, "Y_COORDINATES 1 double\n0")?;
+    writeln!(w, "Z_COORDINATES 1 double\n0")?;
+    writeln!(w, "CELL_DATA {}", ncells)?;
+    let mut fields = vec![("u", u)];
+    if let Some(e) = exact {
+        fields.push(("exact", e));
+    }
+    for &(name, values) in fields.iter() {
+        writeln!(w, "SCALARS {} double 1", name)?;
+        writeln!(w, "LOOKUP_TABLE default")?;
+        for v in &values[..ncells] {
+            writeln!(w, "{}", v)?;
+        }
+    }
+    Ok(())
+}
+
+// Asymptote plot of the computed cell averages over the exact solution
+fn write_plot_asy<W: Write>(
+    f: &mut W,
+    title: &str,
+    x: &[f64],
+    u: &[f64],
+    exact: Option<&[f64]>,
+) -> io::Result<()> {
+    f.write_all(unindent("
+            import graph;
+
+            size(600,400,IgnoreAspect);
+
+            real[] x = {").as_bytes())?;
+
+    for x_elem in cell_centers(x) {
+        write!(f, "{}, ", x_elem)?;
+    }
+
+    f.write_all(unindent("
+            };
+            real[] u = {").as_bytes())?;
+
+    for u_elem in u {
+        write!(f, "{}, ", u_elem)?;
+    }
+
+    f.write_all(b"};\n")?;
+
+    if let Some(e) = exact {
+        f.write_all(b"real[] exact = {")?;
+        for e_elem in e {
+            write!(f, "{}, ", e_elem)?;
+        }
+        f.write_all(unindent(r#"
+            };
+            draw(graph(x, exact), red, "exact");
+            "#).as_bytes())?;
+    }
+
+    write!(f, "{}", unindent(&format!(r#"
+            draw(graph(x, u), blue+linewidth(0.5), "computed", MarkFill[0]);
+            xaxis("$x$", BottomTop(), LeftTicks);
+            yaxis("$u$", LeftRight(), RightTicks);
+            label("{}", point(N), N);
+            attach(legend(), point(E), 20E);
+            "#, title)))?;
+
+    Ok(())
+}
+
+#[test]
+fn output_test() {
+    let x = [0.0f64, 0.5, 1.0];
+    let u = [2.0f64, 0.5];
+    let exact = [2.0f64, 0.0];
+
+    let mut csv = Vec::new();
+    write_csv(&mut csv, 0.25, &x, &u, Some(&exact)).unwrap();
+    assert!(String::from_utf8(csv).unwrap() == "t,x,u,exact\n0.25,0.25,2,2\n0.25,0.75,0.5,0\n");
+
+    let mut vtk = Vec::new();
+    write_vtk(&mut vtk, 0.25, &x, &u, None).unwrap();
+    let vtk = String::from_utf8(vtk).unwrap();
+    let lines = Vec::from_iter(vtk.lines());
+    assert!(lines[0] == "# vtk DataFile Version 3.0" && lines[3] == "DATASET RECTILINEAR_GRID");
+    assert!(vtk.contains("DIMENSIONS 3 1 1\nX_COORDINATES 3 double\n0\n0.5\n1\n"));
+    assert!(vtk.contains("CELL_DATA 2\nSCALARS u double 1\nLOOKUP_TABLE default\n2\n0.5\n"));
+    assert!(!vtk.contains("exact"));
+
+    let mut asy = Vec::new();
+    write_plot_asy(&mut asy, "t = 0.25", &x, &u, Some(&exact)).unwrap();
+    let asy = String::from_utf8(asy).unwrap();
+    assert!(asy.starts_with("import graph;"));
+    assert!(asy.contains("real[] x = {0.25, 0.75, };"));
+    assert!(asy.contains("real[] exact = {2, 0, };"));
+    assert!(asy.contains("label(\"t = 0.25\""));
+}
+
+fn parse_times(text: &str) -> Result<Vec<f64>, String> {
+    text.split(',')
+        .map(|t| match t.trim().parse::<f64>() {
+            Ok(time) if time.is_finite() && time >= 0.0f64 => Ok(time),
+            _ => Err(format!("invalid snapshot time `{}`", t)),
+        })
+        .collect()
+}
+
+// runs the problem once stopping at the snapshot times, writes PREFIX_NNNN.{csv,vtk,asy}
+// for each of them and returns the file names
+fn write_snapshots(
+    options: &Options,
+    problem: &Problem,
+    times: &[f64],
+    flux: &dyn Flux,
+    x: &Vec<f64>,
+) -> io::Result<Vec<String>> {
+    let ncells = x.len() - 1;
+    let exact = problem.exact_solution();
+    let mut u = vec![0.0f64; ncells + 2 * NGHOST];
+    problem.initial_values(x, &mut u);
+
+    let mut times = times.to_vec();
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let mut t = 0.0f64;
+    let mut files = Vec::new();
+    for (k, &tk) in times.iter().enumerate() {
+        if tk > t {
+            t += compute(options, problem, tk - t, usize::max_value(), flux, x, &mut u);
+        }
+        let values = &u[NGHOST..ncells + NGHOST];
+        let exact_values = exact.as_ref().map(|e| {
+            Vec::from_iter(x.windows(2).map(|w| e.cell_average(w[0], w[1], t)))
+        });
+        let exact_values = exact_values.as_ref().map(|e| &e[..]);
+        let name = format!("{}_{:04}", options.output, k);
+
+        // flushed explicitly, dropping a `BufWriter` discards the error of its last write
+        let csv = format!("{}.csv", name);
+        let mut w = BufWriter::new(File::create(&csv)?);
+        write_csv(&mut w, t, x, values, exact_values)?;
+        w.flush()?;
+        let vtk = format!("{}.vtk", name);
+        let mut w = BufWriter::new(File::create(&vtk)?);
+        write_vtk(&mut w, t, x, values, exact_values)?;
+        w.flush()?;
+        let asy = format!("{}.asy", name);
+        let mut w = BufWriter::new(File::create(&asy)?);
+        write_plot_asy(&mut w, &format!("$t = {}$", t), x, values, exact_values)?;
+        w.flush()?;
+        files.extend(vec![csv, vtk, asy]);
+    }
+    Ok(files)
+}
+
+// 2D: u_t + f(u)_x + g(u)_y = 0 on a structured grid, cell (i, j) stored in u[[i, j]]
+// with `i` along x; every step is the Strang splitting X(dt/2) Y(dt) X(dt/2) of 1D sweeps
+// of the method of lines along the grid lines
+
+// solid body rotation tests, one counterclockwise revolution in tmax = 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RotationTest {
+    // a cone of radius 0.2 centered at (0.4, 0) in [-1, 1]^2
+    RotatingCone,
+    // LeVeque's slotted cylinder, cone and smooth hump on [0, 1]^2
+    SolidBodyRotation,
+}
+
+impl FromStr for RotationTest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<RotationTest, String> {
+        match s {
+            "rotating-cone" => Ok(RotationTest::RotatingCone),
+            "solid-body-rotation" => Ok(RotationTest::SolidBodyRotation),
+            _ => Err(format!(
+                "unknown 2D problem `{}`, expected rotating-cone|solid-body-rotation",
+                s
+            )),
+        }
+    }
+}
+
+impl RotationTest {
+    fn domain(&self) -> ((f64, f64), (f64, f64)) {
+        match *self {
+            RotationTest::RotatingCone => ((-1.0f64, 1.0f64), (-1.0f64, 1.0f64)),
+            RotationTest::SolidBodyRotation => ((0.0f64, 1.0f64), (0.0f64, 1.0f64)),
+        }
+    }
+
+    // the center of rotation
+    fn center(&self) -> (f64, f64) {
+        let ((xl, xr), (yl, yr)) = self.domain();
+        (0.5f64 * (xl + xr), 0.5f64 * (yl + yr))
+    }
+
+    fn initial_value(&self, x: f64, y: f64) -> f64 {
+        // distance to (xc, yc) relative to the radius r0, clipped to 1
+        let r = |xc: f64, yc: f64, r0: f64| ((x - xc).hypot(y - yc) / r0).min(1.0f64);
+        match *self {
+            RotationTest::RotatingCone => 1.0f64 - r(0.4, 0.0, 0.2),
+            RotationTest::SolidBodyRotation => {
+                let cylinder = r(0.5, 0.75, 0.15) < 1.0f64 &&
+                    ((x - 0.5f64).abs() >= 0.025f64 || y >= 0.85f64);
+                if cylinder {
+                    1.0f64
+                } else {
+                    1.0f64 - r(0.5, 0.25, 0.15) +
+                        0.25f64 * (1.0f64 + (std::f64::consts::PI * r(0.25, 0.5, 0.15)).cos())
+                }
+            }
+        }
+    }
+}
+
+struct Problem2d {
+    model: Model,
+    test: RotationTest,
+    // angular velocity of the rotation
+    omega: f64,
+    tmax: f64,
+    cfl: f64,
+}
+
+impl Problem2d {
+    fn new(model: Model, test: RotationTest) -> Problem2d {
+        Problem2d {
+            model: model,
+            test: test,
+            omega: 2.0f64 * std::f64::consts::PI,
+            tmax: 1.0f64,
+            cfl: 0.5f64,
+        }
+    }
+
+    // uniform nodes along x and y
+    fn grid(&self, ncells: usize) -> (Vec<f64>, Vec<f64>) {
+        let ((xl, xr), (yl, yr)) = self.test.domain();
+        (mesh::uniform(xl, xr, ncells), mesh::uniform(yl, yr, ncells))
+    }
+
+    // the velocity along `axis` on the grid line through `coord` of the other axis,
+    // constant along the line for the rotation
+    fn velocity(&self, axis: Axis, coord: f64) -> f64 {
+        let (xc, yc) = self.test.center();
+        match axis.index() {
+            0 => -self.omega * (coord - yc),
+            _ => self.omega * (coord - xc),
+        }
+    }
+
+    // the flux of the sweeps along `axis` on the grid lines through `centers`
+    fn line_fluxes(&self, axis: Axis, centers: &[f64]) -> Vec<Box<dyn Flux>> {
+        Vec::from_iter(centers.iter().map(|&c| self.model.flux(self.velocity(axis, c))))
+    }
+
+    fn initial_values(&self, x: &[f64], y: &[f64]) -> Array2<f64> {
+        cell_averages_2d(x, y, |x, y| self.test.initial_value(x, y))
+    }
+
+    // the initial data rotated by omega * t, for the linear model only
+    fn exact_solution(&self, x: &[f64], y: &[f64], t: f64) -> Option<Array2<f64>> {
+        if self.model != Model::Linear {
+            return None;
+        }
+        let (xc, yc) = self.test.center();
+        let (sin, cos) = (self.omega * t).sin_cos();
+        Some(cell_averages_2d(x, y, |x, y| {
+            let (dx, dy) = (x - xc, y - yc);
+            self.test.initial_value(xc + cos * dx + sin * dy, yc - sin * dx + cos * dy)
+        }))
+    }
+}
+
+// cell averages of `g` by the 2x2 point Gauss rule
+fn cell_averages_2d<G: Fn(f64, f64) -> f64>(x: &[f64], y: &[f64], g: G) -> Array2<f64> {
+    let gauss = 0.5f64 / 3.0f64.sqrt();
+    Array2::from_shape_fn((x.len() - 1, y.len() - 1), |(i, j)| {
+        let (mx, hx) = (0.5f64 * (x[i] + x[i + 1]), x[i + 1] - x[i]);
+        let (my, hy) = (0.5f64 * (y[j] + y[j + 1]), y[j + 1] - y[j]);
+        let mut sum = 0.0f64;
+        for &sx in [-gauss, gauss].iter() {
+            for &sy in [-gauss, gauss].iter() {
+                sum += g(mx + sx * hx, my + sy * hy);
+            }
+        }
+        0.25f64 * sum
+    })
+}
+
+// max wave speed over the neighbouring cells of the grid lines along `axis`
+fn max_speed_2d(axis: Axis, fluxes: &[Box<dyn Flux>], u: &Array2<f64>) -> f64 {
+    let other = Axis(1 - axis.index());
+    fluxes.iter().enumerate().fold(0.0f64, |s, (k, f)| {
+        let lane = u.index_axis(other, k);
+        lane.iter()
+            .zip(lane.iter().skip(1))
+            .fold(s, |s, (&a, &b)| s.max(f.max_speed(a, b)))
+    })
+}
+
+// advances every grid line along `axis` by `dt` with one step of the 1D scheme,
+// `dx` holds the padded cell widths along `axis`
+fn sweep(
+    axis: Axis,
+    fluxes: &[Box<dyn Flux>],
+    bc: &BoundaryConditions,
+    numerical_flux: NumericalFlux,
+    limiter: Option<Limiter>,
+    integrator: TimeIntegrator,
+    dt: f64,
+    dx: &[f64],
+    u: &mut Array2<f64>,
+) {
+    let ncells = u.len_of(axis);
+    let other = Axis(1 - axis.index());
+    let mut line = vec![0.0f64; ncells + 2 * NGHOST];
+    let mut u0 = vec![0.0f64; ncells];
+    let mut flux = vec![0.0f64; ncells + 1];
+    let mut res = vec![0.0f64; ncells];
+    for (k, f) in fluxes.iter().enumerate() {
+        let mut lane = u.index_axis_mut(other, k);
+        for (v, &w) in line[NGHOST..].iter_mut().zip(lane.iter()) {
+            *v = w;
+        }
+        ssp_step(
+            ncells,
+            bc,
+            f.as_ref(),
+            numerical_flux,
+            limiter,
+            integrator,
+            dt,
+            dx,
+            &mut line,
+            &mut u0,
+            &mut flux,
+            &mut res,
+        );
+        for (w, &v) in lane.iter_mut().zip(line[NGHOST..].iter()) {
+            *w = v;
+        }
+    }
+}
+
+// Strang splitting of the x and y sweeps, the timestep from the CFL condition of
+// both directions; returns the time reached
+fn do_split_computation(
+    nsteps: usize,
+    tmax: f64,
+    problem: &Problem2d,
+    numerical_flux: NumericalFlux,
+    limiter: Option<Limiter>,
+    integrator: TimeIntegrator,
+    x: &[f64],
+    y: &[f64],
+    u: &mut Array2<f64>,
+) -> f64 {
+    // zero far field: nothing comes in where the flow enters, the upwind
+    // fluxes carry the data out where it leaves
+    let bc = BoundaryConditions {
+        left: Boundary::Inflow(0.0f64),
+        right: Boundary::Inflow(0.0f64),
+    };
+    let fx = problem.line_fluxes(Axis(0), &cell_centers(y));
+    let fy = problem.line_fluxes(Axis(1), &cell_centers(x));
+    let (dx, dy) = (mesh::padded_widths(x, false), mesh::padded_widths(y, false));
+    let (mindx, mindy) = (min_dx(x), min_dx(y));
+
+    let mut istep = 0;
+    let mut t = 0.0f64;
+    while istep < nsteps && t < tmax {
+        let rate = (max_speed_2d(Axis(0), &fx, u) / mindx)
+            .max(max_speed_2d(Axis(1), &fy, u) / mindy);
+        let dt = if rate > 0.0f64 {
+            (problem.cfl / rate).min(tmax - t)
+        } else {
+            tmax - t
+        };
+        let half = 0.5f64 * dt;
+        sweep(Axis(0), &fx, &bc, numerical_flux, limiter, integrator, half, &dx, u);
+        sweep(Axis(1), &fy, &bc, numerical_flux, limiter, integrator, dt, &dy, u);
+        sweep(Axis(0), &fx, &bc, numerical_flux, limiter, integrator, half, &dx, u);
+        t += dt;
+        istep += 1
+    }
+    t
+}
+
+fn error_norms_2d(x: &[f64], y: &[f64], u: &Array2<f64>, exact: &Array2<f64>) -> ErrorNorms {
+    let mut norms = ErrorNorms {
+        l1: 0.0f64,
+        l2: 0.0f64,
+        linf: 0.0f64,
+    };
+    for ((i, j), &v) in u.indexed_iter() {
+        let e = (v - exact[[i, j]]).abs();
+        let area = (x[i + 1] - x[i]) * (y[j + 1] - y[j]);
+        norms.l1 += e * area;
+        norms.l2 += e * e * area;
+        norms.linf = norms.linf.max(e);
+    }
+    norms.l2 = norms.l2.sqrt();
+    norms
+}
+
+// the binary grid format read by `adt_enum`: a byte order flag (0 - little endian),
+// precision (1 - single), value type (1 - real) and the x, y sizes as u32, followed
+// by the values u[[i, j]] with `j` running fastest
+fn write_grid<W: Write>(f: &mut W, u: &Array2<f64>) -> io::Result<()> {
+    let (nx, ny) = u.dim();
+    f.write_all(&[0u8])?;
+    for &v in [1u32, 1u32, nx as u32, ny as u32].iter() {
+        f.write_all(&v.to_le_bytes())?;
+    }
+    for &v in u.iter() {
+        f.write_all(&(v as f32).to_le_bytes())?;
+    }
+    Ok(())
+}
+
+// runs the 2D problem once stopping at the snapshot times, writes PREFIX_NNNN.bin
+// for each of them and returns the file names
+fn write_grid_snapshots(
+    options: &Options,
+    problem: &Problem2d,
+    times: &[f64],
+    x: &[f64],
+    y: &[f64],
+) -> io::Result<Vec<String>> {
+    let limiter = match options.scheme {
+        Scheme::Upwind => None,
+        Scheme::Muscl => Some(options.limiter),
+    };
+    let mut u = problem.initial_values(x, y);
+    let mut times = times.to_vec();
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let mut t = 0.0f64;
+    let mut files = Vec::new();
+    for (k, &tk) in times.iter().enumerate() {
+        if tk > t {
+            t += do_split_computation(
+                usize::max_value(),
+                tk - t,
+                problem,
+                options.numerical_flux,
+                limiter,
+                options.integrator,
+                x,
+                y,
+                &mut u,
+            );
+        }
+        let name = format!("{}_{:04}.bin", options.output, k);
+        let mut w = BufWriter::new(File::create(&name)?);
+        write_grid(&mut w, &u)?;
+        w.flush()?;
+        files.push(name);
+    }
+    Ok(files)
+}
+
+fn main_2d(options: &Options, test: RotationTest) {
+    // the 2D problems are built in and run once on a uniform grid
+    let unsupported = [
+        (!options.config.is_empty(), "--config"),
+        (options.mesh != MeshKind::Uniform, "--mesh"),
+        (!options.mesh_file.is_empty(), "--mesh_file"),
+        (options.convergence, "--convergence"),
+        (options.benchmark, "--benchmark"),
+    ];
+    if let Some(&(_, name)) = unsupported.iter().find(|&&(given, _)| given) {
+        println!("{} is not supported with --problem_2d", name);
+        exit(1);
+    }
+    if options.num_cells < NGHOST {
+        println!("at least {} cells needed", NGHOST);
+        exit(1);
+    }
+    let mut problem = Problem2d::new(options.model, test);
+    if let Some(cfl) = options.cfl {
+        problem.cfl = cfl;
+    }
+    let limiter = match options.scheme {
+        Scheme::Upwind => None,
+        Scheme::Muscl => Some(options.limiter),
+    };
+    let ncells = options.num_cells;
+    let (x, y) = problem.grid(ncells);
+    if options.verbose {
+        println!(
+            "2D problem : {:?} model : {:?} grid : {} x {} cfl : {}",
+            test,
+            problem.model,
+            ncells,
+            ncells,
+            problem.cfl
+        );
+    }
+
+    let initial = problem.initial_values(&x, &y);
+    let mut u = initial.clone();
+    let timer = std::time::Instant::now();
+    let mut t = 0.0f64;
+    for r in 0..options.num_runs {
+        if options.verbose {
+            println!("run number : {}", r);
+        }
+        u = initial.clone();
+        t = do_split_computation(
+            options.num_steps,
+            problem.tmax,
+            &problem,
+            options.numerical_flux,
+            limiter,
+            options.integrator,
+            &x,
+            &y,
+            &mut u,
+        );
+    }
+    let d = timer.elapsed();
+    println!(
+        "elapsed time: {:.2}s.",
+        d.as_secs() as f64 + d.subsec_nanos() as f64 / 1.0e9f64
+    );
+    if options.verbose {
+        let area = (x[1] - x[0]) * (y[1] - y[0]);
+        println!(
+            "mass : {:.6e} -> {:.6e} min : {:.4e} max : {:.4e}",
+            initial.iter().sum::<f64>() * area,
+            u.iter().sum::<f64>() * area,
+            u.iter().cloned().fold(std::f64::MAX, f64::min),
+            u.iter().cloned().fold(std::f64::MIN, f64::max)
+        );
+    }
+    if let Some(exact) = problem.exact_solution(&x, &y, t) {
+        let e = error_norms_2d(&x, &y, &u, &exact);
+        println!(
+            "errors at t = {:.4} : L1 {:.4e} L2 {:.4e} Linf {:.4e}",
+            t,
+            e.l1,
+            e.l2,
+            e.linf
+        );
+    }
+
+    if !options.output.is_empty() {
+        let times = if !options.snapshots.is_empty() {
+            match parse_times(&options.snapshots) {
+                Ok(times) => times,
+                Err(e) => {
+                    println!("{}", e);
+                    exit(1);
+                }
+            }
+        } else {
+            vec![0.0f64, problem.tmax]
+        };
+        match write_grid_snapshots(options, &problem, &times, &x, &y) {
+            Ok(files) => {
+                if options.verbose {
+                    println!("snapshots : {}", files.join(" "));
+                }
+            }
+            Err(e) => {
+                println!("{}: {}", options.output, e);
+                exit(1);
+            }
+        }
+    }
+}
+
+#[test]
+fn rotation_test() {
+    let run = |problem: &Problem2d, ncells: usize, limiter: Option<Limiter>| {
+        let (x, y) = problem.grid(ncells);
+        let mut u = problem.initial_values(&x, &y);
+        let mass = u.iter().sum::<f64>();
+        let t = do_split_computation(
+            usize::max_value(),
+            problem.tmax,
+            problem,
+            NumericalFlux::Godunov,
+            limiter,
+            TimeIntegrator::SspRk2,
+            &x,
+            &y,
+            &mut u,
+        );
+        assert!((t - problem.tmax).abs() < 1e-12);
+        // no new extrema, mass leaves through the far field only
+        assert!(u.iter().all(|&v| v >= -1e-12 && v <= 1.0f64 + 1e-12), "extrema");
+        let lost = (mass - u.iter().sum::<f64>()) / mass;
+        assert!(lost > -1e-12, "mass gained {:e}", -lost);
+        (error_norms_2d(&x, &y, &u, &problem.exact_solution(&x, &y, t).unwrap()), lost)
+    };
+
+    // a quarter turn moves the cone from (0.4, 0) to (0, 0.4)
+    let cone = Problem2d::new(Model::Linear, RotationTest::RotatingCone);
+    let (x, y) = cone.grid(40);
+    let u = cone.exact_solution(&x, &y, 0.25).unwrap();
+    assert!(u[[19, 28]] > 0.7 && u[[28, 20]] == 0.0);
+    assert!(cone.initial_values(&x, &y)[[28, 20]] > 0.7);
+    let e = error_norms_2d(
+        &x,
+        &y,
+        &cone.exact_solution(&x, &y, 1.0).unwrap(),
+        &cone.initial_values(&x, &y),
+    );
+    assert!(e.linf < 1e-12, "a full turn");
+
+    let (coarse, _) = run(&cone, 32, Some(Limiter::VanLeer));
+    let (fine, lost) = run(&cone, 64, Some(Limiter::VanLeer));
+    let order = observed_order(coarse.l1, fine.l1);
+    assert!(order > 1.0, "rotating cone order {}", order);
+    assert!(lost < 1e-8, "mass lost {:e}", lost);
+
+    // first order smears the slotted cylinder into the far field
+    let sbr = Problem2d::new(Model::Linear, RotationTest::SolidBodyRotation);
+    let (upwind, _) = run(&sbr, 50, None);
+    let (muscl, lost) = run(&sbr, 50, Some(Limiter::MC));
+    assert!(muscl.l1 < 0.6 * upwind.l1, "MUSCL {:?} upwind {:?}", muscl, upwind);
+    assert!(lost < 1e-3, "mass lost {:e}", lost);
+}
+
+#[test]
+fn write_grid_test() {
+    let u = Array2::from_shape_fn((3, 2), |(i, j)| (10 * i + j) as f64);
+    let mut buf = Vec::new();
+    write_grid(&mut buf, &u).unwrap();
+    assert!(buf.len() == 1 + 4 * 4 + 3 * 2 * 4);
+    let word = |k: usize| {
+        let mut b = [0u8; 4];
+        b.copy_from_slice(&buf[1 + 4 * k..5 + 4 * k]);
+        b
+    };
+    assert!(buf[0] == 0, "little endian");
+    let header = Vec::from_iter((0..4).map(|k| u32::from_le_bytes(word(k))));
+    assert!(header == vec![1, 1, 3, 2], "single precision real 3 x 2 grid");
+    // u[[i, j]] at (i * ysize + j) as in Grid::read
+    assert!(f32::from_le_bytes(word(4 + 2 * 1 + 1)) == 11.0f32);
+    assert!(f32::from_le_bytes(word(4 + 2 * 2)) == 20.0f32);
+}
+
+fn main() {
+    let mut options = Options::default();
+
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Time elapsing of model PDE for scalar conservation law.");
+        ap.refer(&mut options.verbose).add_option(
+            &["-v", "--verbose"],
+            StoreTrue,
+            "set verbose",
+        );
+        ap.refer(&mut options.num_steps).add_option(
+            &["-t", "--num_steps"],
+            Store,
+            "set number of time steps",
+        );
+        ap.refer(&mut options.num_cells).add_option(
+            &["-n", "--num_cells"],
+            Store,
+            "set number of grid cells",
+        );
+        ap.refer(&mut options.num_runs).add_option(
+            &["-b", "--num_runs"],
+            Store,
+            "set number of runs",
+        );
+        ap.refer(&mut options.scheme).add_option(
+            &["-s", "--scheme"],
+            Store,
+            "set spatial scheme: upwind|muscl",
+        );
+        ap.refer(&mut options.limiter).add_option(
+            &["-l", "--limiter"],
+            Store,
+            "set MUSCL slope limiter: minmod|superbee|vanleer|mc",
+        );
+        ap.refer(&mut options.integrator).add_option(
+            &["-r", "--time_integrator"],
+            Store,
+            "set time integrator: euler|rk2|rk3",
+        );
+        ap.refer(&mut options.model).add_option(
+            &["-m", "--model"],
+            Store,
+            "set flux model: linear|burgers|buckley-leverett",
+        );
+        ap.refer(&mut options.numerical_flux).add_option(
+            &["-f", "--numerical_flux"],
+            Store,
+            "set numerical flux: godunov|eo|lf|rusanov",
+        );
+        ap.refer(&mut options.riemann_problem).add_option(
+            &["-p", "--riemann_problem"],
+            Store,
+            "set initial data: shock|rarefaction",
+        );
+        ap.refer(&mut options.config).add_option(
+            &["--config"],
+            Store,
+            "read the problem from a YAML (or .toml) file instead of --model and --riemann_problem",
+        );
+        ap.refer(&mut options.cfl).add_option(
+            &["--cfl"],
+            StoreOption,
+            "set CFL number",
+        );
+        ap.refer(&mut options.convergence).add_option(
+            &["-c", "--convergence"],
+            StoreTrue,
+            "run a convergence study from --num_cells against the exact solution",
+        );
+        ap.refer(&mut options.levels).add_option(
+            &["--levels"],
+            Store,
+            "set number of mesh refinements of the convergence study",
+        );
+        ap.refer(&mut options.mesh).add_option(
+            &["--mesh"],
+            Store,
+            "set mesh: uniform|stretched|perturbed",
+        );
+        ap.refer(&mut options.stretch).add_option(
+            &["--stretch"],
+            Store,
+            "set ratio of the last to the first cell width of the stretched mesh",
+        );
+        ap.refer(&mut options.perturbation).add_option(
+            &["--perturbation"],
+            Store,
+            "set max node displacement of the perturbed mesh, in cell widths",
+        );
+        ap.refer(&mut options.seed).add_option(
+            &["--seed"],
+            Store,
+            "set random seed of the perturbed mesh",
+        );
+        ap.refer(&mut options.mesh_file).add_option(
+            &["--mesh_file"],
+            Store,
+            "read the mesh nodes from a file, one per line",
+        );
+        ap.refer(&mut options.problem_2d).add_option(
+            &["--problem_2d"],
+            StoreOption,
+            "solve a 2D problem on a num_cells x num_cells grid: rotating-cone|solid-body-rotation",
+        );
+        ap.refer(&mut options.benchmark).add_option(
+            &["--benchmark"],
+            StoreTrue,
+            "time the upwind kernel variants: loop|windows|simd|rayon",
+        );
+        ap.refer(&mut options.threads).add_option(
+            &["--threads"],
+            Store,
+            "set number of rayon threads and subdomains of the benchmark, all cores by default",
+        );
+        ap.refer(&mut options.output).add_option(
+            &["-o", "--output"],
+            Store,
+            "write snapshots to PREFIX_NNNN.{csv,vtk,asy}, PREFIX_NNNN.bin grids in 2D",
+        );
+        ap.refer(&mut options.snapshots).add_option(
+            &["--snapshots"],
+            Store,
+            "set comma separated snapshot times, tmax by default",
+        );
+        match ap.parse_args() {
+            Ok(()) => {}
+            Err(x) => {
+                exit(x);
+            }
+        }
+    }
+
+    if options.verbose {
+        println!(
+            "number of time steps : {} number of grid cells : {} number of runs : {}",
+            options.num_steps,
+            options.num_cells,
+            options.num_runs
+        );
+        println!(
+            "scheme : {:?} limiter : {:?} time integrator : {:?}",
+            options.scheme,
+            options.limiter,
+            options.integrator
+        );
+        if options.config.is_empty() {
+            println!(
+                "model : {:?} numerical flux : {:?} Riemann problem : {:?}",
+                options.model,
+                options.numerical_flux,
+                options.riemann_problem
+            );
+        } else {
+            println!("numerical flux : {:?}", options.numerical_flux);
+        }
+    }
+
+    if let Some(test) = options.problem_2d {
+        main_2d(&options, test);
+        return;
+    }
+
+    let mut problem = if options.config.is_empty() {
+        Problem::riemann(options.model, options.riemann_problem)
+    } else {
+        match Problem::read(&options.config) {
+            Ok(problem) => problem,
+            Err(e) => {
+                println!("{}: {}", options.config, e);
+                exit(1);
+            }
+        }
+    };
+    if let Some(cfl) = options.cfl {
+        problem.cfl = cfl;
+    }
+    if options.verbose {
+        println!("problem : {:?}", problem);
+    }
+    let flux = problem.flux();
+    let exact = problem.exact_solution();
+
+    if options.convergence {
+        match exact {
+            Some(ref exact) => {
+                match convergence_study(&options, &problem, exact, flux.as_ref()) {
+                    Ok(results) => print_convergence_table(&results),
+                    Err(e) => {
+                        println!("{}", e);
+                        exit(1);
+                    }
+                }
+            }
+            None => {
+                println!("no exact solution for the {:?} problem", problem.model);
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    let x = match build_mesh(&options, &problem, 0) {
+        Ok(x) => x,
+        Err(e) => {
+            println!("{}", e);
+            exit(1);
+        }
+    };
+
+    if options.benchmark {
+        if let Err(e) = benchmark(&options, &problem, &x) {
+            println!("{}", e);
+            exit(1);
+        }
+        return;
+    }
+
+    // array bounds:
+    let nsteps = options.num_steps;
+    let ncells = x.len() - 1;
+
+    // work arrays
+    // #   double precision
+    // #  &  u(-2:ncells+1),
+    // #  &  x(0:ncells),
+    // #  &  flux(0:ncells)
+
+    let mut u = vec![0.0f64; ncells + 2 * NGHOST];
+    if options.verbose {
+        println!(
+            "mesh : {} cells on [{}, {}] min dx : {:e} max dx : {:e}",
+            ncells,
+            x[0],
+            x[ncells],
+            min_dx(&x),
+            x.windows(2).map(|w| w[1] - w[0]).fold(0.0f64, f64::max)
+        );
+    }
+
+    // cell containing the jump:
+    if let InitialData::Piecewise { ref breakpoints, .. } = problem.initial {
+        if let Some(&jump) = breakpoints.first() {
+            let ijump = cmp::min(
+                x.iter().take_while(|&&xe| xe <= jump).count().saturating_sub(1),
+                ncells - 1,
+            );
+            if options.verbose {
+                println!("ijump : {}", ijump);
+            }
+        }
+    }
+
+    // range of the initial data
+    problem.initial_values(&x, &mut u);
+    let (lo, hi) = u[NGHOST..ncells + NGHOST]
+        .iter()
+        .fold((std::f64::MAX, std::f64::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+
+    //let d = Duration::span(||{
+    let timer = std::time::Instant::now();
+    let mut t = 0.0f64;
+    for r in 0..options.num_runs {
+        if options.verbose {
+            println!("run number : {}", r);
+        }
+
+        problem.initial_values(&x, &mut u);
+        t = compute(&options, &problem, problem.tmax, nsteps, flux.as_ref(), &x, &mut u);
+    }
+    //});
+    //println!("elapsed time: {:?}s.", timer.elapsed());
+    let d = timer.elapsed();
+    if options.verbose {
+        println!(
+            "smeared cells : {}",
+            smeared_cells(&u[NGHOST..ncells + NGHOST], lo, hi)
+        );
+    }
+    println!(
+        "elapsed time: {:.2}s.",
+        d.as_secs() as f64 + d.subsec_nanos() as f64 / 1.0e9f64
+    );
+    if let Some(exact) = exact {
+        let e = error_norms(&x, &u[NGHOST..ncells + NGHOST], &exact, t);
+        println!(
+            "errors at t = {:.4} : L1 {:.4e} L2 {:.4e} Linf {:.4e}",
+            t,
+            e.l1,
+            e.l2,
+            e.linf
+        );
+    }
+
+    if !options.output.is_empty() {
+        let times = if !options.snapshots.is_empty() {
+            match parse_times(&options.snapshots) {
+                Ok(times) => times,
+                Err(e) => {
+                    println!("{}", e);
+                    exit(1);
+                }
+            }
+        } else if !problem.snapshots.is_empty() {
+            problem.snapshots.clone()
+        } else {
+            vec![problem.tmax]
+        };
+        match write_snapshots(&options, &problem, &times, flux.as_ref(), &x) {
+            Ok(files) => {
+                if options.verbose {
+                    println!("snapshots : {}", files.join(" "));
+                }
+            }
+            Err(e) => {
+                println!("{}: {}", options.output, e);
+                exit(1);
+            }
+        }
     }
-    //});
-    //println!("elapsed time: {:?}s.", timer.elapsed());
-    let d = timer.elapsed();
-    println!(
-        "elapsed time: {:.2}s.",
-        d.as_secs() as f64 + d.subsec_nanos() as f64 / 1.0e9f64
-    );
 }